@@ -5,14 +5,17 @@ use view::View;
 
 pub mod common;
 pub mod controller;
+pub mod language_profile;
 pub mod model;
 pub mod settings;
+pub mod stress_test;
 pub mod ui;
 pub mod view;
 
 mod test_suite;
 mod test_runner;
 mod input_handler;
+mod file_watcher;
 
 use common::*;
 use model::Model;
@@ -23,27 +26,18 @@ pub struct AppState {
     pub controller: MainController,
 }
 
-impl Default for AppState {
-    fn default() -> Self {
-        let settings = Settings::default();
-        let model = Model::new_ref(settings);
-        let controller = MainController::setup(&model);
-        let view = View::from(&model);
-
-        Self {
-            view: Rc::new(view),
-            controller,
-        }
-    }
+/// Settings from the settings file if there is one, otherwise from the
+/// arguments.
+pub fn settings_from(args: &AppArgs) -> Settings {
+    args.settings
+        .as_ref()
+        .map(|settings_path| Settings::load(settings_path))
+        .unwrap_or_else(|| Settings::from(args))
 }
 
 impl From<AppArgs> for AppState {
     fn from(args: AppArgs) -> Self {
-        let settings = args
-            .settings
-            .as_ref()
-            .map(|settings_path| Settings::load(settings_path))
-            .unwrap_or_else(|| Settings::from(&args));
+        let settings = settings_from(&args);
 
         let model: Rc<Model> = Model::new_ref(settings);
         let controller = MainController::setup(&model);
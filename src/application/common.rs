@@ -1,43 +1,38 @@
+use std::collections::BTreeMap;
 use std::time::Duration;
 use serde::{Deserialize, Serialize};
+use crate::application::stress_test::StressTest;
 use tui::{
     style::{Color, Style},
     text::{Span, Spans},
 };
 
+/// Output limit of problems which do not set their own
+pub const DEFAULT_OUTPUT_LIMIT_MB: u64 = 64;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Direction {
     Next,
     Previous,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum Menu {
+    #[default]
     Select,
     Solve,
     Help,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Default)]
 pub enum ProblemDataTab {
     TestCases,
+    #[default]
     Commands,
     Details,
     Performance
 }
 
-impl Default for ProblemDataTab {
-    fn default() -> Self {
-        Self::Commands
-    }
-}
-
-impl Default for Menu {
-    fn default() -> Self {
-        Self::Select
-    }
-}
-
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Difficulty {
     Easy,
@@ -45,21 +40,194 @@ pub enum Difficulty {
     Hard,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Describes how the input of a test case is handed over to the solution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum InputMode {
+    /// Input is fed to the standard input of the solution
+    Stdin,
+    /// Input is appended to the run command as command line arguments
+    #[default]
+    Argv,
+    /// Input is written to a file. Its path replaces `{input}` in the run
+    /// command, or is appended to it if there is no placeholder.
+    File,
+}
+
+/// Decides whether the output of a solution is correct.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum Checker {
+    /// Outputs must be identical
+    Exact,
+    /// Whitespace separated tokens must be identical
+    #[default]
+    Tokens,
+    /// Lines must be identical, ignoring trailing whitespace
+    Lines,
+    /// Tokens which are numbers may differ by the given absolute or relative error
+    Float { absolute: f64, relative: f64 },
+    /// Outputs must contain the same lines, in any order
+    UnorderedLines,
+    /// External program called with paths to the input, the expected output
+    /// and the actual output. Exit code 0 means the answer is correct.
+    Custom { command: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Problem {
     pub name: String,
     pub statement: String,
     pub test_cases: Vec<TestCase>,
     pub difficulty: Difficulty,
+    #[serde(default)]
+    pub input_mode: InputMode,
+    #[serde(default)]
+    pub time_limit_ms: Option<u64>,
+    #[serde(default)]
+    pub memory_limit_mb: Option<u64>,
+    /// Limit of stdout and stderr of the solution, each. Defaults to
+    /// `DEFAULT_OUTPUT_LIMIT_MB`.
+    #[serde(default)]
+    pub output_limit_mb: Option<u64>,
+    #[serde(default)]
+    pub checker: Checker,
+    /// Makes the problem interactive. The interactor is connected to the
+    /// standard input and output of the solution and decides the verdict.
+    #[serde(default)]
+    pub interactor: Option<String>,
+    /// Test cases generated from templates, to measure performance
+    #[serde(default)]
+    pub stress_tests: Vec<StressTest>,
+    /// Namespace of the source the problem was loaded from
+    #[serde(skip)]
+    pub source: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+impl Problem {
+    pub fn run_config(&self, run_script: String, sandbox: bool) -> RunConfig {
+        RunConfig {
+            run_script,
+            run_options: CommandOptions::default(),
+            input_mode: self.input_mode,
+            limits: self.limits(),
+            checker: self.checker.clone(),
+            interactor: self.interactor.clone(),
+            sandbox,
+            clock: Clock::default(),
+        }
+    }
+
+    pub fn limits(&self) -> Limits {
+        Limits {
+            time: self.time_limit_ms.map(Duration::from_millis),
+            memory: self.memory_limit_mb.map(|mb| mb * 1024 * 1024),
+            output: Some(self.output_limit_mb.unwrap_or(DEFAULT_OUTPUT_LIMIT_MB) * 1024 * 1024),
+        }
+    }
+}
+
+/// Which time is used for the time limits and the performance chart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize, clap::ValueEnum)]
+pub enum Clock {
+    /// Real time between the start and the exit of the solution
+    #[default]
+    Wall,
+    /// User and system CPU time of the solution
+    Cpu,
+}
+
+/// Running time of a test case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Timing {
+    pub wall: Duration,
+    pub cpu: Duration,
+}
+
+impl Timing {
+    pub fn get(&self, clock: Clock) -> Duration {
+        match clock {
+            Clock::Wall => self.wall,
+            Clock::Cpu => self.cpu,
+        }
+    }
+}
+
+/// Distribution of the running times of a stress test run repeatedly.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TimeStats {
+    /// Clock the samples were measured with
+    pub clock: Clock,
+    pub samples: Vec<Duration>,
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub stddev: Duration,
+}
+
+impl TimeStats {
+    /// Returns `None` if there are no samples.
+    pub fn from_samples(clock: Clock, samples: Vec<Duration>) -> Option<Self> {
+        let mut sorted = samples.clone();
+        sorted.sort_unstable();
+        let min = *sorted.first()?;
+
+        let median = match sorted.len() % 2 {
+            0 => (sorted[sorted.len() / 2 - 1] + sorted[sorted.len() / 2]) / 2,
+            _ => sorted[sorted.len() / 2],
+        };
+        let secs: Vec<f64> = samples.iter().map(Duration::as_secs_f64).collect();
+        let mean = secs.iter().sum::<f64>() / secs.len() as f64;
+        let variance = secs.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / secs.len() as f64;
+
+        Some(Self {
+            clock,
+            samples,
+            min,
+            median,
+            mean: Duration::from_secs_f64(mean),
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        })
+    }
+}
+
+/// How many times stress tests are run to measure their running time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Benchmark {
+    /// Runs whose times are thrown away
+    pub warmup: usize,
+    /// Runs whose times are measured
+    pub runs: usize,
+}
+
+impl Default for Benchmark {
+    fn default() -> Self {
+        Self { warmup: 0, runs: 1 }
+    }
+}
+
+/// Resource limits applied to every test case of a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Limits {
+    pub time: Option<Duration>,
+    /// In bytes
+    pub memory: Option<u64>,
+    /// Size of stdout and stderr, in bytes
+    pub output: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct TestCase {
     pub id: usize,
     pub complexity: u32,
     pub input: String,
     pub output: String,
     pub is_stress_test: bool,
+    /// Overrides the time limit of the problem
+    #[serde(default)]
+    pub time_limit_ms: Option<u64>,
+    /// Command of a trusted solution, which gives the expected output when
+    /// it is run on the input right before the test case
+    #[serde(skip)]
+    pub reference: Option<String>,
 }
 
 #[memoize::memoize]
@@ -67,14 +235,25 @@ fn into_span_inner(tcs: TestCaseStatus) -> Span<'static> {
     let text = match &tcs {
         TestCaseStatus::Pass { .. } => "🗹 Passed",
         TestCaseStatus::Fail { .. } => "🗷 Failed",
+        TestCaseStatus::TimeLimitExceeded { .. } => "⏱ Time Limit",
+        TestCaseStatus::MemoryLimitExceeded { .. } => "▦ Memory Limit",
+        TestCaseStatus::OutputLimitExceeded { .. } => "▤ Output Limit",
+        TestCaseStatus::SandboxViolation { .. } => "⛔ Sandbox",
         TestCaseStatus::Running => "⌛ Running",
         TestCaseStatus::Cancelled => "⚠ Cancelled",
         TestCaseStatus::NotRun => "🯄 Not Run",
-        TestCaseStatus::Err { .. } => "‼ Error",
+        TestCaseStatus::RuntimeError { .. } => "☠ Runtime Error",
+        TestCaseStatus::Err { .. } => "‼ Internal Error",
     };
     let style = match &tcs {
         TestCaseStatus::Pass { .. } => Style::default().fg(Color::Green),
-        TestCaseStatus::Fail { .. } | TestCaseStatus::Err { .. } => Style::default().fg(Color::Red),
+        TestCaseStatus::Fail { .. }
+        | TestCaseStatus::TimeLimitExceeded { .. }
+        | TestCaseStatus::MemoryLimitExceeded { .. }
+        | TestCaseStatus::OutputLimitExceeded { .. }
+        | TestCaseStatus::SandboxViolation { .. }
+        | TestCaseStatus::RuntimeError { .. } => Style::default().fg(Color::Red),
+        TestCaseStatus::Err { .. } => Style::default().fg(Color::Magenta),
         TestCaseStatus::Cancelled | TestCaseStatus::Running => Style::default().fg(Color::Yellow),
         TestCaseStatus::NotRun => Style::default().fg(Color::Gray),
     };
@@ -84,24 +263,81 @@ fn into_span_inner(tcs: TestCaseStatus) -> Span<'static> {
 impl TestCaseStatus {
     pub fn into_detailed<'a>(self) -> Vec<Spans<'a>> {
         match self {
-            Self::Pass { .. } => vec![Spans::from("Test case passed!")],
-            Self::Fail { expected, actual, time: _, complexity: _ } => {
+            Self::Pass { time, complexity: _, max_rss, stats } => {
+                let mut lines = vec![
+                    Spans::from("Test case passed!"),
+                    Spans::from(format!("Time:     {}", format_timing(time))),
+                    Spans::from(format!("Memory:   {}", format_memory(max_rss))),
+                ];
+                if let Some(stats) = stats {
+                    lines.append(&mut stats.into_detailed());
+                }
+                lines
+            }
+            Self::Fail { expected, actual, message, time, complexity: _, max_rss } => {
                 vec![
                     Spans::from(Span::styled(
                         "Test failed!",
                         Style::default().fg(Color::Red),
                     )),
+                    Spans::from("Reason:   ".to_string() + &message),
                     Spans::from("Expected: ".to_string() + &expected),
                     Spans::from("Actual:   ".to_string() + &actual),
+                    Spans::from(format!("Time:     {}", format_timing(time))),
+                    Spans::from(format!("Memory:   {}", format_memory(max_rss))),
                 ]
             }
+            Self::TimeLimitExceeded { limit, elapsed, complexity: _ } => vec![
+                Spans::from(Span::styled(
+                    "Time limit exceeded!",
+                    Style::default().fg(Color::Red),
+                )),
+                Spans::from(format!("Limit:   {} ms", limit.as_millis())),
+                Spans::from(format!("Elapsed: {} (process was killed)", format_timing(elapsed))),
+            ],
+            Self::MemoryLimitExceeded { limit, max_rss } => vec![
+                Spans::from(Span::styled(
+                    "Memory limit exceeded!",
+                    Style::default().fg(Color::Red),
+                )),
+                Spans::from(format!("Limit:       {}", format_memory(limit))),
+                Spans::from(format!("Peak memory: {}", format_memory(max_rss))),
+            ],
+            Self::OutputLimitExceeded { limit } => vec![
+                Spans::from(Span::styled(
+                    "Output limit exceeded!",
+                    Style::default().fg(Color::Red),
+                )),
+                Spans::from(format!("Limit:   {} (process was killed)", format_memory(limit))),
+            ],
+            Self::SandboxViolation { message } => vec![
+                Spans::from(Span::styled(
+                    "Sandbox violation!",
+                    Style::default().fg(Color::Red),
+                )),
+                Spans::from(message),
+            ],
             Self::Running => vec![Spans::from(
-                "Test case is still running... (stderr/out directed to /tmp/algonds_stderr/out",
+                "Test case is still running...",
             )],
             Self::Cancelled => vec![Spans::from("Test case was cancelled")],
             Self::NotRun => vec![Spans::from("Test case was not yet run")],
+            Self::RuntimeError { signal, code, core_dumped, stderr } => {
+                let mut lines = vec![
+                    Spans::from(Span::styled(
+                        "Runtime error!",
+                        Style::default().fg(Color::Red),
+                    )),
+                    Spans::from("Reason:   ".to_string() + &describe_exit(signal, code, core_dumped)),
+                ];
+                if !stderr.trim().is_empty() {
+                    lines.push(Spans::from("Stderr:"));
+                    lines.extend(stderr.lines().map(|line| Spans::from(line.to_string())));
+                }
+                lines
+            }
             Self::Err { err_msg } => vec![
-                Spans::from("The following error occured:"),
+                Spans::from("The runner failed, this is not caused by your solution:"),
                 Spans::from(err_msg),
             ],
         }
@@ -110,14 +346,98 @@ impl TestCaseStatus {
     pub fn into_span(self) -> Span<'static> {
         into_span_inner(self)
     }
+
+    /// Whether the test case ran and did not pass.
+    pub fn is_failure(&self) -> bool {
+        matches!(
+            self,
+            Self::Fail { .. }
+                | Self::TimeLimitExceeded { .. }
+                | Self::MemoryLimitExceeded { .. }
+                | Self::OutputLimitExceeded { .. }
+                | Self::SandboxViolation { .. }
+                | Self::RuntimeError { .. }
+                | Self::Err { .. }
+        )
+    }
+
+    /// Time and memory used by the test case, if it has finished normally.
+    pub fn usage(&self, clock: Clock) -> Option<String> {
+        match self {
+            Self::Pass { stats: Some(stats), max_rss, .. } => {
+                Some(format!("{} median, {}", format_time(stats.median), format_memory(*max_rss)))
+            }
+            Self::Pass { time, max_rss, .. } | Self::Fail { time, max_rss, .. } => {
+                Some(format!("{}, {}", format_time(time.get(clock)), format_memory(*max_rss)))
+            }
+            _ => None,
+        }
+    }
 }
 
-impl Default for TestCaseStatus {
-    fn default() -> Self {
-        Self::NotRun
+impl TimeStats {
+    fn into_detailed<'a>(self) -> Vec<Spans<'a>> {
+        let clock = match self.clock {
+            Clock::Wall => "wall",
+            Clock::Cpu => "CPU",
+        };
+        let samples: Vec<String> = self.samples.into_iter().map(format_time).collect();
+        vec![
+            Spans::from(""),
+            Spans::from(format!("Benchmark of {} runs ({} time):", samples.len(), clock)),
+            Spans::from(format!("Min:      {}", format_time(self.min))),
+            Spans::from(format!("Median:   {}", format_time(self.median))),
+            Spans::from(format!("Mean:     {}", format_time(self.mean))),
+            Spans::from(format!("Stddev:   {}", format_time(self.stddev))),
+            Spans::from(format!("Samples:  {}", samples.join(", "))),
+        ]
+    }
+}
+
+fn describe_exit(signal: Option<i32>, code: Option<i32>, core_dumped: bool) -> String {
+    match (signal, code) {
+        (Some(signal), _) => format!(
+            "Killed by signal {} ({}){}",
+            signal,
+            signal_name(signal),
+            if core_dumped { ", core dumped" } else { "" }
+        ),
+        (None, Some(code)) => format!("Exited with code {}", code),
+        (None, None) => "Exited abnormally".to_string(),
     }
 }
 
+fn signal_name(signal: i32) -> &'static str {
+    match signal {
+        libc::SIGSEGV => "SIGSEGV, segmentation fault",
+        libc::SIGABRT => "SIGABRT, aborted",
+        libc::SIGFPE => "SIGFPE, arithmetic error",
+        libc::SIGBUS => "SIGBUS, bus error",
+        libc::SIGILL => "SIGILL, illegal instruction",
+        libc::SIGKILL => "SIGKILL, killed",
+        libc::SIGTERM => "SIGTERM, terminated",
+        libc::SIGPIPE => "SIGPIPE, broken pipe",
+        libc::SIGXCPU => "SIGXCPU, CPU time limit",
+        libc::SIGXFSZ => "SIGXFSZ, file size limit",
+        libc::SIGSYS => "SIGSYS, bad system call",
+        libc::SIGTRAP => "SIGTRAP, trap",
+        libc::SIGINT => "SIGINT, interrupted",
+        _ => "unknown signal",
+    }
+}
+
+fn format_time(time: Duration) -> String {
+    format!("{} ms", time.as_millis())
+}
+
+fn format_timing(timing: Timing) -> String {
+    format!("{} wall, {} CPU", format_time(timing.wall), format_time(timing.cpu))
+}
+
+fn format_memory(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
 impl From<&Difficulty> for Span<'_> {
     fn from(diff: &Difficulty) -> Self {
         match diff {
@@ -132,33 +452,293 @@ impl From<&Difficulty> for Span<'_> {
 pub enum InputField {
     CompileCommand,
     RunCommand,
+    SolutionPath,
+    Environment,
+    WorkingDir,
+    Generator,
+    BruteForce,
+}
+
+/// Which test cases are run, the statuses of the others are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunSubset {
+    All,
+    /// Test cases whose last run did not pass
+    Failed,
+    /// The test case with the given id
+    Selected(usize),
+    /// Test cases which are not stress tests
+    Samples,
 }
 
 #[derive(Clone)]
+#[allow(clippy::enum_variant_names)]
 pub enum RunRequest {
-    PleaseRun(RunDetails),
+    PleaseRun(Box<RunDetails>),
     PleaseStop,
+    PleaseExit,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub enum TestCaseStatus {
-    Pass { time: Duration, complexity: u32 },
-    Fail { expected: String, actual: String, time: Duration, complexity: u32 },
+    Pass { time: Timing, complexity: u32, max_rss: u64, stats: Option<TimeStats> },
+    Fail { expected: String, actual: String, message: String, time: Timing, complexity: u32, max_rss: u64 },
+    TimeLimitExceeded { limit: Duration, elapsed: Timing, complexity: u32 },
+    MemoryLimitExceeded { limit: u64, max_rss: u64 },
+    OutputLimitExceeded { limit: u64 },
+    SandboxViolation { message: String },
+    /// The solution crashed or exited with a non-zero code
+    RuntimeError { signal: Option<i32>, code: Option<i32>, core_dumped: bool, stderr: String },
+    /// Error of the runner itself, not caused by the solution
     Err { err_msg: String },
     Cancelled,
     Running,
+    #[default]
     NotRun,
 }
 
+/// State of the compilation step of the latest run.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum CompilationStatus {
+    #[default]
+    NotStarted,
+    Compiling,
+    /// Compiled from scratch
+    Compiled,
+    /// Nothing changed since the last successful build, so it was reused
+    UpToDate,
+    CompilationError { diagnostics: String },
+    Cancelled,
+}
+
+impl CompilationStatus {
+    pub fn into_detailed<'a>(self) -> Vec<Spans<'a>> {
+        match self {
+            Self::NotStarted => vec![Spans::from("Compilation: not started")],
+            Self::Compiling => vec![Spans::from(Span::styled(
+                "Compilation: compiling…",
+                Style::default().fg(Color::Yellow),
+            ))],
+            Self::Compiled => vec![Spans::from(Span::styled(
+                "Compilation: rebuilt",
+                Style::default().fg(Color::Green),
+            ))],
+            Self::UpToDate => vec![Spans::from(Span::styled(
+                "Compilation: up to date",
+                Style::default().fg(Color::Green),
+            ))],
+            Self::Cancelled => vec![Spans::from("Compilation: cancelled")],
+            Self::CompilationError { diagnostics } => {
+                let mut lines = vec![Spans::from(Span::styled(
+                    "Compilation failed! Test cases were not run.",
+                    Style::default().fg(Color::Red),
+                ))];
+                lines.extend(diagnostics.lines().map(|line| Spans::from(line.to_string())));
+                lines
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
-pub struct RunResponse {
-    pub id: usize,
+pub enum RunResponse {
+    TestCase { id: usize, status: TestCaseStatus },
+    Compilation(CompilationStatus),
+    Transcript { id: usize, transcript: String },
+    Differential(DifferentialStatus),
+    Shrink(ShrinkStatus),
+    /// Input found by differential testing or shrinking, on which the
+    /// solution fails
+    NewTestCase { test_case: TestCase, status: TestCaseStatus },
+}
+
+/// Progress of minimising the input of a failing test case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShrinkStatus {
+    /// `size` is the length of the smallest failing input found so far
+    Running { original: usize, size: usize, tries: usize },
+    /// The minimised input was added as test case `id`, unless nothing
+    /// could be removed
+    Done { original: usize, size: usize, id: Option<usize> },
+    Failed { err_msg: String },
+    Cancelled,
+}
+
+impl ShrinkStatus {
+    pub fn into_detailed(self) -> Vec<Spans<'static>> {
+        match self {
+            Self::Running { original, size, tries } => vec![Spans::from(Span::styled(
+                format!("Shrinking test case {}: {} bytes so far after {} tries", original, size, tries),
+                Style::default().fg(Color::Yellow),
+            ))],
+            Self::Done { original, size, id: Some(id) } => vec![Spans::from(Span::styled(
+                format!("Test case {} shrunk to {} bytes, added as test case {}", original, size, id),
+                Style::default().fg(Color::Green),
+            ))],
+            Self::Done { original, .. } => vec![Spans::from(format!(
+                "Input of test case {} is already minimal",
+                original
+            ))],
+            Self::Failed { err_msg } => {
+                let mut lines = vec![Spans::from(Span::styled(
+                    "Shrinking failed:",
+                    Style::default().fg(Color::Magenta),
+                ))];
+                lines.extend(err_msg.lines().map(|line| Spans::from(line.to_string())));
+                lines
+            }
+            Self::Cancelled => vec![Spans::from("Shrinking cancelled")],
+        }
+    }
+}
+
+/// Failing test case whose input is minimised.
+#[derive(Debug, Clone)]
+pub struct ShrinkTest {
+    pub test_case: TestCase,
     pub status: TestCaseStatus,
+    /// Gives the expected outputs of smaller inputs. Needed if the solution
+    /// gave a wrong answer, other failures keep the expected output.
+    pub brute_force: Option<String>,
+    /// Id given to the minimised test case
+    pub id: usize,
+}
+
+/// Progress of comparing the solution with a brute force one on generated
+/// inputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DifferentialStatus {
+    Running { passed: usize },
+    /// The solution failed on an input, which was added as test case `id`
+    Found { passed: usize, id: usize },
+    Failed { passed: usize, err_msg: String },
+    Cancelled { passed: usize },
+}
+
+impl DifferentialStatus {
+    pub fn into_detailed(self) -> Vec<Spans<'static>> {
+        match self {
+            Self::Running { passed } => vec![Spans::from(Span::styled(
+                format!("Comparing with brute force: {} inputs passed", passed),
+                Style::default().fg(Color::Yellow),
+            ))],
+            Self::Found { passed, id } => vec![Spans::from(Span::styled(
+                format!("Mismatch after {} inputs, added as test case {}", passed, id),
+                Style::default().fg(Color::Red),
+            ))],
+            Self::Failed { passed, err_msg } => {
+                let mut lines = vec![Spans::from(Span::styled(
+                    format!("Comparing with brute force failed after {} inputs:", passed),
+                    Style::default().fg(Color::Magenta),
+                ))];
+                lines.extend(err_msg.lines().map(|line| Spans::from(line.to_string())));
+                lines
+            }
+            Self::Cancelled { passed } => vec![Spans::from(format!(
+                "Comparing with brute force cancelled after {} inputs",
+                passed
+            ))],
+        }
+    }
+}
+
+/// Commands used to look for an input on which the solution fails.
+#[derive(Debug, Clone)]
+pub struct DifferentialTest {
+    /// Prints a random input, gets the seed as its last argument
+    pub generator: String,
+    /// Slow but trusted solution, whose output is expected
+    pub brute_force: String,
+    /// Id given to the input found
+    pub id: usize,
+}
+
+/// How a compile or run command is started.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandOptions {
+    /// Run the command through `sh -c` instead of splitting it into arguments
+    pub shell: bool,
+    /// Variables added to the environment of the command
+    pub env: BTreeMap<String, String>,
+    /// Directory the command is started in, the current one if not set
+    pub working_dir: Option<String>,
+}
+
+/// Describes how every test case of a run is executed and judged.
+#[derive(Debug, Clone, Default)]
+pub struct RunConfig {
+    pub run_script: String,
+    pub run_options: CommandOptions,
+    pub input_mode: InputMode,
+    pub limits: Limits,
+    pub checker: Checker,
+    /// Command of the interactor, if the problem is interactive
+    pub interactor: Option<String>,
+    /// Whether the solution runs isolated from the rest of the system
+    pub sandbox: bool,
+    /// Time which is compared against the time limit
+    pub clock: Clock,
 }
 
 #[derive(Clone)]
 pub struct RunDetails {
     pub compile_script: String,
-    pub run_script: String,
+    pub compile_options: CommandOptions,
+    pub config: RunConfig,
+    /// Replaces `{src}` in the commands
+    pub source: Option<String>,
+    /// Files and directories the compile step depends on, besides the source
+    pub compile_inputs: Vec<String>,
+    /// How many test cases can run at the same time
+    pub jobs: usize,
+    /// How many times stress tests are run
+    pub benchmark: Benchmark,
+    /// Where to keep inputs and outputs of the run. If not set, they are
+    /// removed once the run is over.
+    pub artifacts_dir: Option<String>,
     pub test_cases: Vec<TestCase>,
+    /// Instead of the test cases, compares the solution with a brute force
+    /// one on generated inputs
+    pub differential: Option<DifferentialTest>,
+    /// Instead of the test cases, minimises the input of a failing one
+    pub shrink: Option<ShrinkTest>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(samples: &[u64]) -> Vec<Duration> {
+        samples.iter().copied().map(Duration::from_millis).collect()
+    }
+
+    #[test]
+    fn time_stats_of_an_odd_number_of_samples() {
+        let stats = TimeStats::from_samples(Clock::Wall, millis(&[30, 10, 20])).unwrap();
+        assert_eq!(stats.min, Duration::from_millis(10));
+        assert_eq!(stats.median, Duration::from_millis(20));
+        assert_eq!(stats.mean, Duration::from_millis(20));
+        assert_eq!(stats.samples, millis(&[30, 10, 20]));
+    }
+
+    #[test]
+    fn time_stats_of_an_even_number_of_samples() {
+        let stats = TimeStats::from_samples(Clock::Cpu, millis(&[40, 10, 20, 30])).unwrap();
+        assert_eq!(stats.median, Duration::from_millis(25));
+        assert_eq!(stats.mean, Duration::from_millis(25));
+        // Population standard deviation, sqrt(125) ms
+        assert_eq!(stats.stddev.as_micros(), 11_180);
+    }
+
+    #[test]
+    fn time_stats_of_equal_samples_do_not_deviate() {
+        let stats = TimeStats::from_samples(Clock::Wall, millis(&[7, 7, 7])).unwrap();
+        assert_eq!(stats.stddev, Duration::ZERO);
+        assert_eq!(stats.median, Duration::from_millis(7));
+    }
+
+    #[test]
+    fn time_stats_need_samples() {
+        assert_eq!(TimeStats::from_samples(Clock::Wall, Vec::new()), None);
+    }
 }
@@ -10,19 +10,14 @@ mod select_screen_controller;
 use problem_screen_controller::ProblemScreenController;
 use select_screen_controller::SelectScreenController;
 
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Eq, PartialEq, Debug, Default)]
 pub enum AfterEvent {
     Quit,
+    #[default]
     DoRefresh,
     NoRefresh,
 }
 
-impl Default for AfterEvent {
-    fn default() -> Self {
-        DoRefresh
-    }
-}
-
 pub trait Controller {
     fn setup(model_ref: &Rc<Model>) -> Self;
     fn react_to_event(&self, event: Event) -> AfterEvent;
@@ -98,6 +93,14 @@ impl MainController {
         DoRefresh
     }
 
+    /// Re-runs the test cases if the solution changed in watch mode.
+    pub fn react_to_file_changes(&self) -> AfterEvent {
+        match self.model.rerun_if_files_changed() {
+            true => DoRefresh,
+            false => NoRefresh,
+        }
+    }
+
     pub fn kill_all_processes(&self) {
         self.model.kill_all_processes()
     }
@@ -1,5 +1,5 @@
 use crate::application::{
-    common::{Direction, InputField, Menu, ProblemDataTab},
+    common::{Direction, InputField, Menu, ProblemDataTab, RunSubset},
     controller::input_controller::InputController,
     controller::AfterEvent,
     controller::AfterEvent::*,
@@ -26,10 +26,19 @@ impl Controller for ProblemScreenController {
         }
 
         if let Event::Key(key) = event {
+            if self.model.problem_data_tab.get() == ProblemDataTab::Commands {
+                match key.code {
+                    KeyCode::Left => return self.switch_language(Direction::Previous),
+                    KeyCode::Right => return self.switch_language(Direction::Next),
+                    _ => {}
+                }
+            }
+
             if self.model.problem_data_tab.get() == ProblemDataTab::Details {
                 match key.code {
                     KeyCode::Left => return self.select_prev_test_case(),
                     KeyCode::Right => return self.select_next_test_case(),
+                    KeyCode::Char('n') => return self.shrink_selected_test_case(),
                     _ => {}
                 }
             }
@@ -37,12 +46,24 @@ impl Controller for ProblemScreenController {
             return match key.code {
                 KeyCode::Char('c') => self.edit(InputField::CompileCommand),
                 KeyCode::Char('r') => self.edit(InputField::RunCommand),
+                KeyCode::Char('f') => self.edit(InputField::SolutionPath),
+                KeyCode::Char('v') => self.edit(InputField::Environment),
+                KeyCode::Char('k') => self.edit(InputField::WorkingDir),
+                KeyCode::Char('g') => self.edit(InputField::Generator),
+                KeyCode::Char('b') => self.edit(InputField::BruteForce),
+                KeyCode::Char('m') => self.run_differential(),
+                KeyCode::Char('x') => self.toggle_shell(true),
+                KeyCode::Char('y') => self.toggle_shell(false),
+                KeyCode::Char('w') => self.toggle_watch(),
                 KeyCode::Char('s') => self.display_under_problem(ProblemDataTab::Commands),
                 KeyCode::Char('t') => self.display_under_problem(ProblemDataTab::TestCases),
                 KeyCode::Char('d') => self.display_under_problem(ProblemDataTab::Details),
                 KeyCode::Char('p') => self.display_under_problem(ProblemDataTab::Performance),
                 KeyCode::Char('q') => self.change_menu(Menu::Select),
                 KeyCode::Enter => self.run_test_cases(),
+                KeyCode::Char('a') => self.run_subset(RunSubset::Failed),
+                KeyCode::Char('o') => self.run_selected_test_case(),
+                KeyCode::Char('e') => self.run_subset(RunSubset::Samples),
                 KeyCode::Backspace => self.cancel_test_cases(),
                 _ => NoRefresh,
             };
@@ -69,6 +90,26 @@ impl ProblemScreenController {
         DoRefresh
     }
 
+    fn toggle_watch(&self) -> AfterEvent {
+        self.model.toggle_watch();
+        DoRefresh
+    }
+
+    /// Toggles shell mode of the compile command, or of the run command.
+    fn toggle_shell(&self, compile: bool) -> AfterEvent {
+        self.display_under_problem(ProblemDataTab::Commands);
+        match compile {
+            true => self.model.toggle_compile_in_shell(),
+            false => self.model.toggle_run_in_shell(),
+        }
+        DoRefresh
+    }
+
+    fn switch_language(&self, dir: Direction) -> AfterEvent {
+        self.model.switch_language(dir);
+        DoRefresh
+    }
+
     fn change_menu(&self, new_menu: Menu) -> AfterEvent {
         self.model.go_to(new_menu);
         DoRefresh
@@ -79,6 +120,27 @@ impl ProblemScreenController {
         DoRefresh
     }
 
+    fn run_subset(&self, subset: RunSubset) -> AfterEvent {
+        self.model.run_test_cases(subset);
+        DoRefresh
+    }
+
+    fn run_differential(&self) -> AfterEvent {
+        self.display_under_problem(ProblemDataTab::Commands);
+        self.model.run_differential();
+        DoRefresh
+    }
+
+    fn shrink_selected_test_case(&self) -> AfterEvent {
+        self.model.shrink_selected_test_case();
+        DoRefresh
+    }
+
+    fn run_selected_test_case(&self) -> AfterEvent {
+        self.model.run_selected_test_case();
+        DoRefresh
+    }
+
     fn select_next_test_case(&self) -> AfterEvent {
         self.model.select_test_case(Direction::Next);
         DoRefresh
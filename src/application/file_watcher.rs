@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::ffi::{CString, OsStr, OsString};
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Changes closer to each other than this are treated as a single one, as
+/// editors often write a file in several steps.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+const EVENTS: u32 = libc::IN_CLOSE_WRITE
+    | libc::IN_MOVED_TO
+    | libc::IN_CREATE
+    | libc::IN_DELETE
+    | libc::IN_MODIFY;
+
+/// Watches files and directories with inotify. Files are watched through
+/// their directory, so that editors replacing the file on save are noticed.
+pub struct FileWatcher {
+    fd: libc::c_int,
+    /// Names of the watched files in every watched directory, `None` if the
+    /// whole directory is watched.
+    watched: HashMap<libc::c_int, Option<Vec<OsString>>>,
+    last_change: Option<Instant>,
+}
+
+impl FileWatcher {
+    pub fn new(paths: &[String]) -> Result<Self, String> {
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if fd == -1 {
+            return Err(io::Error::last_os_error().to_string());
+        }
+        let mut watcher = Self {
+            fd,
+            watched: HashMap::new(),
+            last_change: None,
+        };
+
+        for path in paths {
+            watcher.add(Path::new(path))?;
+        }
+        Ok(watcher)
+    }
+
+    fn add(&mut self, path: &Path) -> Result<(), String> {
+        let (dir, name) = match (path.is_dir(), path.file_name()) {
+            (false, Some(name)) => {
+                let parent = path.parent().filter(|parent| !parent.as_os_str().is_empty());
+                (parent.unwrap_or_else(|| Path::new(".")), Some(name.to_os_string()))
+            }
+            _ => (path, None),
+        };
+
+        let dir_name = CString::new(dir.as_os_str().as_bytes()).map_err(|err| err.to_string())?;
+        let wd = unsafe { libc::inotify_add_watch(self.fd, dir_name.as_ptr(), EVENTS) };
+        if wd == -1 {
+            let err = io::Error::last_os_error();
+            return Err(format!("Cannot watch {}: {}", path.display(), err));
+        }
+
+        let names = self.watched.entry(wd).or_insert_with(|| Some(Vec::new()));
+        match (names, name) {
+            (Some(names), Some(name)) => names.push(name),
+            (names, None) => *names = None,
+            (None, Some(_)) => {}
+        }
+        Ok(())
+    }
+
+    /// Returns true once, after a change to one of the watched paths was
+    /// followed by a quiet period.
+    pub fn has_changed(&mut self) -> bool {
+        if self.read_events() {
+            self.last_change = Some(Instant::now());
+        }
+
+        match self.last_change {
+            Some(last_change) if last_change.elapsed() >= DEBOUNCE => {
+                self.last_change = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Reads all pending events and returns whether any of them concerns a
+    /// watched path.
+    fn read_events(&self) -> bool {
+        const HEADER: usize = std::mem::size_of::<libc::inotify_event>();
+        let mut buf = [0u8; 4096];
+        let mut relevant = false;
+
+        loop {
+            let n = unsafe { libc::read(self.fd, buf.as_mut_ptr().cast(), buf.len()) };
+            if n <= 0 {
+                return relevant;
+            }
+
+            let mut offset = 0;
+            while offset + HEADER <= n as usize {
+                // SAFETY: the kernel writes whole events into the buffer
+                let event: libc::inotify_event =
+                    unsafe { std::ptr::read_unaligned(buf[offset..].as_ptr().cast()) };
+                let name_bytes = &buf[offset + HEADER..offset + HEADER + event.len as usize];
+                let name_len = name_bytes.iter().position(|&b| b == 0).unwrap_or(name_bytes.len());
+                let name = OsStr::from_bytes(&name_bytes[..name_len]);
+
+                relevant |= match self.watched.get(&event.wd) {
+                    Some(Some(names)) => names.iter().any(|watched| watched == name),
+                    Some(None) => true,
+                    None => false,
+                };
+                offset += HEADER + event.len as usize;
+            }
+        }
+    }
+
+    /// Number of watched files and directories.
+    pub fn number_of_watched(&self) -> usize {
+        self.watched
+            .values()
+            .map(|names| names.as_ref().map_or(1, Vec::len))
+            .sum()
+    }
+}
+
+impl Drop for FileWatcher {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.fd) };
+    }
+}
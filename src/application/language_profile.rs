@@ -0,0 +1,88 @@
+use std::path::Path;
+
+use crate::application::common::Direction;
+
+/// Compile and run commands of a programming language. The commands may use
+/// `{src}` for the solution file, `{tmpdir}` for the private directory of
+/// the build and `{bin}` for the compiled program inside of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LanguageProfile {
+    pub name: &'static str,
+    pub extensions: &'static [&'static str],
+    pub compile: &'static str,
+    pub run: &'static str,
+}
+
+pub const PROFILES: [LanguageProfile; 7] = [
+    LanguageProfile {
+        name: "Rust",
+        extensions: &["rs"],
+        compile: "rustc -O -o {bin} {src}",
+        run: "{bin}",
+    },
+    LanguageProfile {
+        name: "C++",
+        extensions: &["cpp", "cc", "cxx"],
+        compile: "g++ -O2 -std=c++17 -o {bin} {src}",
+        run: "{bin}",
+    },
+    LanguageProfile {
+        name: "C",
+        extensions: &["c"],
+        compile: "gcc -O2 -o {bin} {src} -lm",
+        run: "{bin}",
+    },
+    LanguageProfile {
+        name: "Python",
+        extensions: &["py"],
+        compile: "python3 -m py_compile {src}",
+        run: "python3 {src}",
+    },
+    LanguageProfile {
+        name: "Java",
+        extensions: &["java"],
+        compile: "javac -d {tmpdir} {src}",
+        run: "java -cp {tmpdir} Main",
+    },
+    LanguageProfile {
+        name: "Go",
+        extensions: &["go"],
+        compile: "go build -o {bin} {src}",
+        run: "{bin}",
+    },
+    LanguageProfile {
+        name: "Haskell",
+        extensions: &["hs"],
+        compile: "ghc -O2 -outputdir {tmpdir} -o {bin} {src}",
+        run: "{bin}",
+    },
+];
+
+impl LanguageProfile {
+    pub fn by_name(name: &str) -> Option<&'static Self> {
+        PROFILES
+            .iter()
+            .find(|profile| profile.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Picks the profile from the extension of the solution file.
+    pub fn for_source(path: &str) -> Option<&'static Self> {
+        let extension = Path::new(path).extension()?.to_str()?;
+        PROFILES
+            .iter()
+            .find(|profile| profile.extensions.contains(&extension))
+    }
+
+    /// Profile next to the one called `current`, wrapping around. Starts from
+    /// the first one if there is no current profile.
+    pub fn neighbour(current: Option<&str>, dir: Direction) -> &'static Self {
+        let position = current.and_then(|name| PROFILES.iter().position(|profile| profile.name == name));
+        let n = PROFILES.len();
+        let next = match (position, dir) {
+            (None, _) => 0,
+            (Some(i), Direction::Next) => (i + 1) % n,
+            (Some(i), Direction::Previous) => (i + n - 1) % n,
+        };
+        &PROFILES[next]
+    }
+}
@@ -1,7 +1,10 @@
 use crate::application::test_suite::TestSuite;
 use crate::data::load;
 use crate::application::common::*;
+use crate::application::file_watcher::FileWatcher;
 use crate::application::input_handler::InputHandler;
+use crate::application::language_profile::LanguageProfile;
+use crate::application::settings::parse_env;
 use crate::application::Settings;
 
 use std::cell::Cell;
@@ -21,24 +24,32 @@ pub struct Model {
     pub selected_test_case: Cell<usize>,
 
     db: Db,
+    /// Why some of the sources could not be loaded
+    source_errors: Vec<String>,
     test_suite: TestSuite,
     list_state: RefCell<ListState>,
+    watcher: RefCell<Option<FileWatcher>>,
+    watch_error: RefCell<Option<String>>,
 }
 
 impl Model {
     pub fn new_ref(settings: Settings) -> Rc<Self> {
         let mut list_state = ListState::default();
         list_state.select(Some(0));
+        let (db, source_errors) = load(&settings.sources);
 
         Rc::new(Model {
             problem_data_tab: Cell::default(),
-            db: load(&settings.db_path),
+            db,
+            source_errors,
             input_handler: InputHandler::default(),
             settings: RefCell::new(settings.clone()),
             current_menu: Cell::default(),
             test_suite: TestSuite::new(),
             list_state: RefCell::new(list_state),
             selected_test_case: Cell::default(),
+            watcher: RefCell::default(),
+            watch_error: RefCell::default(),
         })
     }
     
@@ -69,7 +80,7 @@ impl Model {
         let n_problems = self.total_problems();
 
         let next_id = if dir == Direction::Next {
-            std::cmp::min(id + 1, n_problems - 1)
+            std::cmp::min(id + 1, n_problems.saturating_sub(1))
         } else if id > 0 {
             id - 1
         } else {
@@ -85,10 +96,13 @@ impl Model {
     }
 
     pub fn go_to(&self, menu: Menu) {
-        self.current_menu.set(menu);
         if menu == Menu::Solve {
-            self.test_suite.set_test_cases_from(self.current_problem())
+            match self.current_problem() {
+                Some(problem) => self.test_suite.set_test_cases_from(problem),
+                None => return,
+            }
         }
+        self.current_menu.set(menu);
     }
 
     pub fn cancel_editing_field(&self) {
@@ -100,14 +114,36 @@ impl Model {
     }
 
     pub fn run_all_test_cases(&self) {
-        let compile_script = self.settings.borrow().compilation_step.clone();
-        let run_script = self.settings.borrow().run_step.clone();
-        self.test_suite.run(compile_script, run_script);
+        self.run_test_cases(RunSubset::All);
+    }
+
+    pub fn run_test_cases(&self, subset: RunSubset) {
+        self.test_suite.run(&self.settings.borrow(), subset);
+    }
+
+    /// Compares the solution with the brute force one on generated inputs.
+    pub fn run_differential(&self) {
+        self.test_suite.run_differential(&self.settings.borrow());
+    }
+
+    /// Minimises the input of the test case selected in the Details tab.
+    pub fn shrink_selected_test_case(&self) {
+        self.test_suite.shrink(&self.settings.borrow(), self.selected_test_case.get());
+    }
+
+    /// Runs only the test case selected in the Details tab.
+    pub fn run_selected_test_case(&self) {
+        self.run_test_cases(RunSubset::Selected(self.selected_test_case.get()));
     }
 
-    pub fn current_problem(&self) -> Rc<Problem> {
+    /// `None` if no problem could be loaded.
+    pub fn current_problem(&self) -> Option<Rc<Problem>> {
         let id: usize = self.list_state.borrow().selected().unwrap_or(0);
-        self.db.get(id).unwrap().clone()
+        self.db.get(id).cloned()
+    }
+
+    pub fn get_source_errors(&self) -> Vec<String> {
+        self.source_errors.clone()
     }
 
     pub fn get_problems(&self) -> Vec<Rc<Problem>> {
@@ -119,6 +155,11 @@ impl Model {
         match field {
             InputField::CompileCommand => settings.compilation_step.clone(),
             InputField::RunCommand => settings.run_step.clone(),
+            InputField::SolutionPath => settings.solution.clone().unwrap_or_default(),
+            InputField::Environment => settings.env_line(),
+            InputField::WorkingDir => settings.working_dir.clone().unwrap_or_default(),
+            InputField::Generator => settings.generator.clone().unwrap_or_default(),
+            InputField::BruteForce => settings.brute_force.clone().unwrap_or_default(),
         }
     }
 
@@ -134,14 +175,144 @@ impl Model {
             match field {
                 InputField::CompileCommand => settings.compilation_step = finished_input,
                 InputField::RunCommand => settings.run_step = finished_input,
+                InputField::SolutionPath => {
+                    if let Some(profile) = LanguageProfile::for_source(&finished_input) {
+                        settings.use_profile(profile);
+                    }
+                    settings.solution = Some(finished_input).filter(|path| !path.is_empty());
+                }
+                InputField::Environment => settings.env = parse_env(&finished_input),
+                InputField::WorkingDir => {
+                    settings.working_dir = Some(finished_input).filter(|dir| !dir.is_empty())
+                }
+                InputField::Generator => {
+                    settings.generator = Some(finished_input).filter(|command| !command.is_empty())
+                }
+                InputField::BruteForce => {
+                    settings.brute_force = Some(finished_input).filter(|command| !command.is_empty())
+                }
             }
         }
+
+        drop(settings);
+        if field == Some(InputField::SolutionPath) && self.is_watching() {
+            self.start_watching();
+        }
+    }
+
+    pub fn toggle_compile_in_shell(&self) {
+        let mut settings = self.settings.borrow_mut();
+        settings.compile_in_shell = !settings.compile_in_shell;
+    }
+
+    pub fn toggle_run_in_shell(&self) {
+        let mut settings = self.settings.borrow_mut();
+        settings.run_in_shell = !settings.run_in_shell;
+    }
+
+    pub fn is_watching(&self) -> bool {
+        self.watcher.borrow().is_some()
+    }
+
+    pub fn toggle_watch(&self) {
+        if self.is_watching() {
+            *self.watcher.borrow_mut() = None;
+        } else {
+            self.start_watching();
+        }
+    }
+
+    /// Watches the solution and the other inputs of the compile step.
+    fn start_watching(&self) {
+        let settings = self.settings.borrow();
+        let paths: Vec<String> = settings
+            .solution
+            .iter()
+            .chain(settings.compile_inputs.iter())
+            .cloned()
+            .collect();
+
+        let watcher = match paths.is_empty() {
+            true => Err("Set the path to the solution first".to_string()),
+            false => FileWatcher::new(&paths),
+        };
+        match watcher {
+            Ok(watcher) => {
+                *self.watcher.borrow_mut() = Some(watcher);
+                *self.watch_error.borrow_mut() = None;
+            }
+            Err(err_msg) => {
+                *self.watcher.borrow_mut() = None;
+                *self.watch_error.borrow_mut() = Some(err_msg);
+            }
+        }
+    }
+
+    /// Describes the watch mode for the Setup tab.
+    pub fn watch_status(&self) -> String {
+        if let Some(err_msg) = &*self.watch_error.borrow() {
+            return format!("off ({})", err_msg);
+        }
+        match &*self.watcher.borrow() {
+            Some(watcher) => format!("on, watching {} path(s)", watcher.number_of_watched()),
+            None => "off".to_string(),
+        }
+    }
+
+    /// Cancels the current run and starts a new one if a watched file has
+    /// changed. Returns true if it did.
+    pub fn rerun_if_files_changed(&self) -> bool {
+        if self.current_menu.get() != Menu::Solve {
+            return false;
+        }
+        let changed = match &mut *self.watcher.borrow_mut() {
+            Some(watcher) => watcher.has_changed(),
+            None => false,
+        };
+        if changed {
+            self.cancel_run();
+            self.run_all_test_cases();
+        }
+        changed
+    }
+
+    /// Switches to the next or previous language profile and fills in both
+    /// commands from it.
+    pub fn switch_language(&self, dir: Direction) {
+        let mut settings = self.settings.borrow_mut();
+        let profile = LanguageProfile::neighbour(settings.language.as_deref(), dir);
+        settings.use_profile(profile);
     }
 
     pub fn get_test_cases(&self) -> Vec<TestCaseStatus> {
         self.test_suite.get_test_cases()
     }
 
+    pub fn get_stress_test_error(&self) -> Option<String> {
+        self.test_suite.get_stress_test_error()
+    }
+
+    pub fn get_differential_status(&self) -> Option<DifferentialStatus> {
+        self.test_suite.get_differential_status()
+    }
+
+    pub fn get_shrink_status(&self) -> Option<ShrinkStatus> {
+        self.test_suite.get_shrink_status()
+    }
+
+    /// Input of the selected test case, if it was added while solving.
+    pub fn ad_hoc_input_for_selected_test_case(&self) -> Option<String> {
+        let id = self.selected_test_case.get();
+        match self.test_suite.is_ad_hoc(id) {
+            true => self.test_suite.get_input(id),
+            false => None,
+        }
+    }
+
+    pub fn get_compilation_status(&self) -> CompilationStatus {
+        self.test_suite.get_compilation_status()
+    }
+
     pub fn details_for_selected_test_case(&self) -> (usize, TestCaseStatus) {
         let selected_id = self.selected_test_case.get();
         (
@@ -150,12 +321,16 @@ impl Model {
         )
     }
 
+    pub fn transcript_for_selected_test_case(&self) -> Option<String> {
+        self.test_suite.get_nth_transcript(self.selected_test_case.get())
+    }
+
     pub fn check_for_changes(&self) -> bool {
         self.test_suite.check_for_changes()
     }
 
     pub fn kill_all_processes(&self) {
-        self.test_suite.stop()
+        self.test_suite.shutdown()
     }
 
     pub fn health_check(&self) {
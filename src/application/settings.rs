@@ -1,23 +1,121 @@
+use crate::application::common::{Clock, CommandOptions};
+use crate::application::language_profile::LanguageProfile;
 use crate::AppArgs;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
 
-const DB_ADDR: &str = "https://raw.githubusercontent.com/MaciejWas/algonds/main/src/data/db.yaml";
+/// Source standing for the problems downloaded by `update`, or the ones
+/// built into the app if there are none.
+pub const DEFAULT_SOURCE: &str = "default";
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Settings {
-    pub db_path: String,
+    /// Files, directories and web links with the problems, merged into one
+    /// catalogue. `default` stands for the ones downloaded by `update`.
+    #[serde(default = "default_sources", alias = "db_path", deserialize_with = "one_or_many")]
+    pub sources: Vec<String>,
     pub compilation_step: String,
     pub run_step: String,
+    /// Run the compile step through `sh -c`
+    #[serde(default)]
+    pub compile_in_shell: bool,
+    /// Run the run step through `sh -c`
+    #[serde(default)]
+    pub run_in_shell: bool,
+    /// Environment variables of the compile and run steps
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Directory the compile and run steps are started in
+    #[serde(default)]
+    pub working_dir: Option<String>,
+    /// Prints a random input for differential testing, given a seed
+    #[serde(default)]
+    pub generator: Option<String>,
+    /// Slow but trusted solution the solution is compared with
+    #[serde(default)]
+    pub brute_force: Option<String>,
+    /// Path to the solution, which replaces `{src}` in the commands
+    #[serde(default)]
+    pub solution: Option<String>,
+    /// Name of the language profile the commands come from
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Files and directories the compile step depends on besides the
+    /// solution. Compilation is skipped if none of them changed.
+    #[serde(default)]
+    pub compile_inputs: Vec<String>,
     pub pretty: bool,
+    /// Number of test cases run in parallel
+    #[serde(default = "default_jobs")]
+    pub jobs: usize,
+    /// If set, inputs and outputs of every run are kept in this directory
+    #[serde(default)]
+    pub artifacts_dir: Option<String>,
+    /// Run solutions in a sandbox
+    #[serde(default)]
+    pub sandbox: bool,
+    /// Time used for the time limits and the performance chart
+    #[serde(default)]
+    pub clock: Clock,
+    /// How many times every stress test is run to measure its time
+    #[serde(default = "default_benchmark_runs")]
+    pub benchmark_runs: usize,
+    /// Runs of every stress test before the measured ones
+    #[serde(default)]
+    pub warmup_runs: usize,
+}
+
+fn default_sources() -> Vec<String> {
+    vec![DEFAULT_SOURCE.to_string()]
+}
+
+/// Accepts a single source too, like in older settings files.
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(source) => vec![source],
+        OneOrMany::Many(sources) => sources,
+    })
+}
+
+fn default_benchmark_runs() -> usize {
+    1
+}
+
+fn default_jobs() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
-            db_path: DB_ADDR.to_string(),
+            sources: default_sources(),
             compilation_step: "echo hi there".to_string(),
             run_step: "echo hi there".to_string(),
+            compile_in_shell: false,
+            run_in_shell: false,
+            env: BTreeMap::new(),
+            working_dir: None,
+            generator: None,
+            brute_force: None,
+            solution: None,
+            language: None,
+            compile_inputs: Vec::new(),
             pretty: true,
+            jobs: default_jobs(),
+            artifacts_dir: None,
+            sandbox: false,
+            clock: Clock::default(),
+            benchmark_runs: default_benchmark_runs(),
+            warmup_runs: 0,
         }
     }
 }
@@ -26,8 +124,22 @@ impl From<&AppArgs> for Settings {
     fn from(args: &AppArgs) -> Self {
         let mut settings = Self::default();
 
-        if let Some(db_path) = &args.db_path {
-            settings.db_path = db_path.clone()
+        if let Some(solution) = &args.solution {
+            settings.solution = Some(solution.clone())
+        }
+
+        settings.compile_inputs = args.compile_input.clone();
+
+        let profile = match &args.language {
+            Some(name) => LanguageProfile::by_name(name),
+            None => args.solution.as_deref().and_then(LanguageProfile::for_source),
+        };
+        if let Some(profile) = profile {
+            settings.use_profile(profile)
+        }
+
+        if !args.db_path.is_empty() {
+            settings.sources = args.db_path.clone();
         }
 
         if let Some(comp_step) = &args.compilation_step {
@@ -38,12 +150,81 @@ impl From<&AppArgs> for Settings {
             settings.run_step = run_step.clone()
         }
 
+        if let Some(working_dir) = &args.working_dir {
+            settings.working_dir = Some(working_dir.clone())
+        }
+
+        if let Some(generator) = &args.generator {
+            settings.generator = Some(generator.clone())
+        }
+
+        if let Some(brute_force) = &args.brute_force {
+            settings.brute_force = Some(brute_force.clone())
+        }
+
+        settings.env = args.env.iter().filter_map(|arg| parse_env_var(arg)).collect();
+        settings.compile_in_shell = args.compile_in_shell;
+        settings.run_in_shell = args.run_in_shell;
+
+        if let Some(jobs) = args.jobs {
+            settings.jobs = jobs
+        }
+
+        if let Some(artifacts_dir) = &args.keep_artifacts {
+            settings.artifacts_dir = Some(artifacts_dir.clone())
+        }
+
+        if let Some(clock) = args.clock {
+            settings.clock = clock
+        }
+
+        if let Some(runs) = args.benchmark_runs {
+            settings.benchmark_runs = runs
+        }
+
+        if let Some(runs) = args.warmup_runs {
+            settings.warmup_runs = runs
+        }
+
+        settings.sandbox = args.sandbox;
         settings.pretty = !args.disable_unicode;
         settings
     }
 }
 
 impl Settings {
+    /// Replaces both commands with the ones of `profile`.
+    pub fn use_profile(&mut self, profile: &LanguageProfile) {
+        self.language = Some(profile.name.to_string());
+        self.compilation_step = profile.compile.to_string();
+        self.run_step = profile.run.to_string();
+    }
+
+    pub fn compile_options(&self) -> CommandOptions {
+        self.command_options(self.compile_in_shell)
+    }
+
+    pub fn run_options(&self) -> CommandOptions {
+        self.command_options(self.run_in_shell)
+    }
+
+    fn command_options(&self, shell: bool) -> CommandOptions {
+        CommandOptions {
+            shell,
+            env: self.env.clone(),
+            working_dir: self.working_dir.clone(),
+        }
+    }
+
+    /// Environment variables as `KEY=VALUE` pairs separated by spaces.
+    pub fn env_line(&self) -> String {
+        self.env
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, shlex::quote(value)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
     pub fn load(path: &str) -> Settings {
         if is_web_link(path) {
             Self::load_from_web(path)
@@ -67,3 +248,42 @@ impl Settings {
 fn is_web_link(text: &str) -> bool {
     text.starts_with("http")
 }
+
+/// Parses space separated `KEY=VALUE` pairs, values may be quoted. Words
+/// without `=` are ignored.
+pub fn parse_env(line: &str) -> BTreeMap<String, String> {
+    shlex::split(line)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|word| parse_env_var(&word))
+        .collect()
+}
+
+/// Parses a single `KEY=VALUE` pair, the value is taken as it is.
+fn parse_env_var(word: &str) -> Option<(String, String)> {
+    let (key, value) = word.split_once('=')?;
+    Some((key.to_string(), value.to_string())).filter(|(key, _)| !key.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn env_args_keep_their_spaces() {
+        let args = AppArgs::parse_from(["algonds", "--env", "A=hello world", "--env", "B=x=y", "--env", "C", "run"]);
+        let env = Settings::from(&args).env;
+        assert_eq!(env.get("A").map(String::as_str), Some("hello world"));
+        assert_eq!(env.get("B").map(String::as_str), Some("x=y"));
+        assert_eq!(env.len(), 2);
+    }
+
+    #[test]
+    fn env_line_is_split_into_words() {
+        let env = parse_env("A='hello world' B=2 C");
+        assert_eq!(env.get("A").map(String::as_str), Some("hello world"));
+        assert_eq!(env.get("B").map(String::as_str), Some("2"));
+        assert_eq!(env.len(), 2);
+    }
+}
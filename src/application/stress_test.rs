@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+use crate::application::common::TestCase;
+
+mod formula;
+
+pub use formula::Formula;
+
+/// Family of stress tests generated from templates, one test case for every
+/// size `n`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StressTest {
+    /// Values of `n` for which a test case is generated
+    pub sizes: Vec<u64>,
+    pub input: Template,
+    /// Expected output built from `n` like the input
+    #[serde(default)]
+    pub output: Option<Template>,
+    /// Command of a trusted solution giving the expected output instead. It
+    /// gets the input the same way as the tested solution.
+    #[serde(default)]
+    pub reference: Option<String>,
+    /// Complexity of the test case, shown in the performance chart. `n` if not set.
+    #[serde(default)]
+    pub complexity: Option<Formula>,
+    /// Overrides the time limit of the problem
+    #[serde(default)]
+    pub time_limit_ms: Option<u64>,
+}
+
+/// Builds a text from the size `n`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Template {
+    /// Fixed text
+    Text(String),
+    /// Number computed from `n`
+    Formula(Formula),
+    /// `text` repeated `times` times, with `separator` in between
+    Repeat {
+        text: String,
+        times: Formula,
+        #[serde(default)]
+        separator: String,
+    },
+    /// Numbers from `from` to `to` inclusive, with `separator` in between
+    Range {
+        from: Formula,
+        to: Formula,
+        #[serde(default = "default_separator")]
+        separator: String,
+    },
+    /// All templates one after another
+    Concat(Vec<Template>),
+}
+
+fn default_separator() -> String {
+    " ".to_string()
+}
+
+impl Template {
+    pub fn render(&self, n: u64) -> Result<String, String> {
+        match self {
+            Template::Text(text) => Ok(text.clone()),
+            Template::Formula(formula) => Ok(formula.eval(n)?.to_string()),
+            Template::Repeat { text, times, separator } => {
+                let times = usize::try_from(times.eval(n)?).map_err(|_| "Negative number of repetitions")?;
+                Ok(vec![text.as_str(); times].join(separator))
+            }
+            Template::Range { from, to, separator } => {
+                let numbers: Vec<String> = (from.eval(n)?..=to.eval(n)?).map(|i| i.to_string()).collect();
+                Ok(numbers.join(separator))
+            }
+            Template::Concat(templates) => templates.iter().map(|template| template.render(n)).collect(),
+        }
+    }
+}
+
+impl StressTest {
+    /// Test cases for all sizes, numbered from `first_id`. Outputs of the
+    /// reference solution are left to the runner.
+    fn generate(&self, first_id: usize) -> Result<Vec<TestCase>, String> {
+        self.sizes
+            .iter()
+            .enumerate()
+            .map(|(i, &n)| {
+                let input = self.input.render(n)?;
+                let (output, reference) = match (&self.output, &self.reference) {
+                    (Some(template), None) => (template.render(n)?, None),
+                    (None, Some(command)) => (String::new(), Some(command.clone())),
+                    _ => return Err("A stress test needs either `output` or `reference`".to_string()),
+                };
+                let complexity = match &self.complexity {
+                    Some(formula) => formula.eval(n)?,
+                    None => n as i128,
+                };
+                Ok(TestCase {
+                    id: first_id + i,
+                    complexity: u32::try_from(complexity).map_err(|_| format!("Complexity {} is out of range", complexity))?,
+                    input,
+                    output,
+                    is_stress_test: true,
+                    time_limit_ms: self.time_limit_ms,
+                    reference,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Test cases of all stress tests, numbered from `first_id`.
+pub fn generate(stress_tests: &[StressTest], first_id: usize) -> Result<Vec<TestCase>, String> {
+    let mut test_cases = Vec::new();
+    for stress_test in stress_tests {
+        test_cases.extend(stress_test.generate(first_id + test_cases.len())?);
+    }
+    Ok(test_cases)
+}
@@ -0,0 +1,268 @@
+use serde::{Deserialize, Serialize};
+
+/// Integer expression of the size `n` of a stress test, like `n * (n + 1) / 2`.
+/// Supports `+`, `-`, `*`, `/`, `%`, `^`, parentheses and the functions
+/// `log2`, `sqrt`, `min` and `max`. Parsed when the problem is loaded, so that
+/// typos are noticed early.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "FormulaSource", into = "String")]
+pub struct Formula {
+    source: String,
+    expr: Expr,
+}
+
+/// Formulas which are just a number are written without quotes in YAML.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FormulaSource {
+    Number(i64),
+    Text(String),
+}
+
+impl TryFrom<FormulaSource> for Formula {
+    type Error = String;
+
+    fn try_from(source: FormulaSource) -> Result<Self, String> {
+        match source {
+            FormulaSource::Number(number) => Formula::parse(&number.to_string()),
+            FormulaSource::Text(text) => Formula::parse(&text),
+        }
+    }
+}
+
+impl From<Formula> for String {
+    fn from(formula: Formula) -> Self {
+        formula.source
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(i128),
+    Size,
+    Negate(Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+impl Formula {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut parser = Parser { text: source.as_bytes(), pos: 0 };
+        let expr = parser.expression()?;
+        parser.skip_whitespace();
+        if parser.pos < parser.text.len() {
+            return Err(parser.error("Unexpected character"));
+        }
+        Ok(Self { source: source.to_string(), expr })
+    }
+
+    pub fn eval(&self, n: u64) -> Result<i128, String> {
+        eval(&self.expr, n as i128).map_err(|err| format!("{} (in `{}` for n = {})", err, self.source, n))
+    }
+}
+
+fn eval(expr: &Expr, n: i128) -> Result<i128, String> {
+    let overflow = || "Overflow".to_string();
+    match expr {
+        Expr::Number(number) => Ok(*number),
+        Expr::Size => Ok(n),
+        Expr::Negate(expr) => eval(expr, n)?.checked_neg().ok_or_else(overflow),
+        Expr::Binary(op, left, right) => {
+            let (left, right) = (eval(left, n)?, eval(right, n)?);
+            match op {
+                '+' => left.checked_add(right).ok_or_else(overflow),
+                '-' => left.checked_sub(right).ok_or_else(overflow),
+                '*' => left.checked_mul(right).ok_or_else(overflow),
+                '/' => left.checked_div(right).ok_or_else(|| "Division by zero".to_string()),
+                '%' => left.checked_rem(right).ok_or_else(|| "Division by zero".to_string()),
+                '^' => {
+                    let exponent = u32::try_from(right).map_err(|_| "Invalid exponent".to_string())?;
+                    left.checked_pow(exponent).ok_or_else(overflow)
+                }
+                _ => Err(format!("Unknown operator {}", op)),
+            }
+        }
+        Expr::Call(name, args) => {
+            let args = args.iter().map(|arg| eval(arg, n)).collect::<Result<Vec<_>, _>>()?;
+            match (name.as_str(), args.as_slice()) {
+                ("log2", [x]) if *x > 0 => Ok(x.ilog2() as i128),
+                ("log2", [_]) => Err("log2 of a number which is not positive".to_string()),
+                ("sqrt", [x]) if *x >= 0 => Ok(x.isqrt()),
+                ("sqrt", [_]) => Err("sqrt of a negative number".to_string()),
+                ("min", [x, y]) => Ok(*x.min(y)),
+                ("max", [x, y]) => Ok(*x.max(y)),
+                _ => Err(format!("Unknown function {} with {} arguments", name, args.len())),
+            }
+        }
+    }
+}
+
+/// Recursive descent parser, from the lowest precedence to the highest.
+struct Parser<'a> {
+    text: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> String {
+        format!(
+            "{} at position {} of formula `{}`",
+            message,
+            self.pos,
+            String::from_utf8_lossy(self.text)
+        )
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.text.get(self.pos).is_some_and(u8::is_ascii_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.text.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        let found = self.peek() == Some(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    /// Sum or difference of terms
+    fn expression(&mut self) -> Result<Expr, String> {
+        let mut expr = self.term()?;
+        while let Some(op @ (b'+' | b'-')) = self.peek() {
+            self.pos += 1;
+            expr = Expr::Binary(op as char, Box::new(expr), Box::new(self.term()?));
+        }
+        Ok(expr)
+    }
+
+    /// Product, quotient or remainder of negated powers
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        while let Some(op @ (b'*' | b'/' | b'%')) = self.peek() {
+            self.pos += 1;
+            expr = Expr::Binary(op as char, Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    /// Binds looser than a power, so that `-n^2` is `-(n^2)`
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat(b'-') {
+            return Ok(Expr::Negate(Box::new(self.unary()?)));
+        }
+        self.power()
+    }
+
+    /// Right associative power
+    fn power(&mut self) -> Result<Expr, String> {
+        let base = self.atom()?;
+        if self.eat(b'^') {
+            return Ok(Expr::Binary('^', Box::new(base), Box::new(self.unary()?)));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        match self.peek() {
+            Some(b'(') => {
+                self.pos += 1;
+                let expr = self.expression()?;
+                match self.eat(b')') {
+                    true => Ok(expr),
+                    false => Err(self.error("Expected `)`")),
+                }
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let digits = self.take_while(|c| c.is_ascii_digit() || c == b'_');
+                let number = digits.replace('_', "").parse().map_err(|_| self.error("Number is too big"))?;
+                Ok(Expr::Number(number))
+            }
+            Some(c) if c.is_ascii_alphabetic() => {
+                let name = self.take_while(|c| c.is_ascii_alphanumeric());
+                if !self.eat(b'(') {
+                    return match name.as_str() {
+                        "n" => Ok(Expr::Size),
+                        _ => Err(self.error(&format!("Unknown variable `{}`", name))),
+                    };
+                }
+                let mut args = vec![self.expression()?];
+                while self.eat(b',') {
+                    args.push(self.expression()?);
+                }
+                match self.eat(b')') {
+                    true => Ok(Expr::Call(name, args)),
+                    false => Err(self.error("Expected `)`")),
+                }
+            }
+            Some(_) => Err(self.error("Unexpected character")),
+            None => Err(self.error("Unexpected end")),
+        }
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(u8) -> bool) -> String {
+        let start = self.pos;
+        while self.text.get(self.pos).is_some_and(|&c| predicate(c)) {
+            self.pos += 1;
+        }
+        String::from_utf8_lossy(&self.text[start..self.pos]).into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(source: &str, n: u64) -> Result<i128, String> {
+        Formula::parse(source)?.eval(n)
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(eval("1 + 2 * 3", 0), Ok(7));
+        assert_eq!(eval("(1 + 2) * 3", 0), Ok(9));
+        assert_eq!(eval("2 * 3 ^ 2", 0), Ok(18));
+        assert_eq!(eval("-2 ^ 2", 0), Ok(-4));
+        assert_eq!(eval("n * (n + 1) / 2", 10), Ok(55));
+        assert_eq!(eval("n % 4 + 1", 10), Ok(3));
+        assert_eq!(eval("max(n, 3) - min(n, 3)", 10), Ok(7));
+        assert_eq!(eval("n * log2(n) + sqrt(n)", 16), Ok(68));
+    }
+
+    #[test]
+    fn associativity() {
+        assert_eq!(eval("10 - 3 - 2", 0), Ok(5));
+        assert_eq!(eval("100 / 10 / 5", 0), Ok(2));
+        assert_eq!(eval("2 ^ 3 ^ 2", 0), Ok(512));
+        assert_eq!(eval("--n", 7), Ok(7));
+    }
+
+    #[test]
+    fn error_positions() {
+        let error = |source: &str| Formula::parse(source).unwrap_err();
+        assert_eq!(error("n +"), "Unexpected end at position 3 of formula `n +`");
+        assert_eq!(error("n $ 2"), "Unexpected character at position 2 of formula `n $ 2`");
+        assert_eq!(error("(n + 1"), "Expected `)` at position 6 of formula `(n + 1`");
+        assert_eq!(error("2 * m"), "Unknown variable `m` at position 5 of formula `2 * m`");
+        assert_eq!(error("max(n, 1"), "Expected `)` at position 8 of formula `max(n, 1`");
+    }
+
+    #[test]
+    fn checked_arithmetic() {
+        assert_eq!(eval("n / (n - 5)", 5), Err("Division by zero (in `n / (n - 5)` for n = 5)".to_string()));
+        assert_eq!(eval("n % 0", 5), Err("Division by zero (in `n % 0` for n = 5)".to_string()));
+        assert_eq!(eval("n ^ 200", 10), Err("Overflow (in `n ^ 200` for n = 10)".to_string()));
+        let overflow = format!("Overflow (in `n * n * n` for n = {})", u64::MAX);
+        assert_eq!(eval("n * n * n", u64::MAX), Err(overflow));
+        assert_eq!(eval("2 ^ -1", 0), Err("Invalid exponent (in `2 ^ -1` for n = 0)".to_string()));
+        assert!(eval("log2(n)", 0).is_err());
+        assert!(eval("sqrt(-n)", 1).is_err());
+        assert!(eval("99999999999999999999999999999999999999999", 0).is_err());
+    }
+}
@@ -1,29 +1,111 @@
 use std::fmt::Display;
 use std::process::Command;
 
+use crate::application::common::CommandOptions;
+
+mod benchmark;
+mod build_cache;
+mod checker;
 mod code_runner;
+mod compilation;
+mod differential;
+mod interaction;
 mod remote_runner;
+mod resource_usage;
+mod run_directory;
 mod runnable_test_case;
+mod sandbox;
+mod shrinker;
 mod test_case_io;
 
+pub use benchmark::Benchmarks;
+pub use build_cache::BuildCache;
 pub use code_runner::CodeRunner;
+pub use compilation::Compilation;
+pub use differential::{Differential, Progress};
+pub use interaction::Interaction;
 pub use remote_runner::RemoteRunner;
+pub use run_directory::RunDirectory;
 pub use runnable_test_case::RunnableTestCase;
+pub use shrinker::{ShrinkProgress, Shrinker};
 pub use test_case_io::TestCaseIO;
 
 fn to_string<T: Display>(thing: T) -> String {
     format!("{}", thing)
 }
 
+const EMPTY_COMMAND: &str = "As of now, all compile/run commands must not be empty";
+
 fn parse_command(text: impl Into<String>) -> Result<Command, String> {
     let whole_command = text.into();
-    if whole_command.is_empty() {
-        return Err("As of now, all compile/run command must noe be empty".into());
-    }
-
     let command_and_args =
-        shlex::split(&whole_command).ok_or_else(|| "Failed to parse command:\"{command}\"".to_string())?;
-    let mut command = Command::new(&command_and_args[0]);
-    command.args(&command_and_args[1..]);
+        shlex::split(&whole_command).ok_or_else(|| format!("Failed to parse command: \"{whole_command}\""))?;
+    let (program, args) = command_and_args.split_first().ok_or_else(|| EMPTY_COMMAND.to_string())?;
+    let mut command = Command::new(program);
+    command.args(args);
+    Ok(command)
+}
+
+/// Builds a compile or run command. In shell mode the whole text is passed
+/// to `sh -c`, so that pipes, redirects and `&&` can be used.
+fn build_command(text: impl Into<String>, options: &CommandOptions) -> Result<Command, String> {
+    let text = text.into();
+    let mut command = match options.shell {
+        true if text.trim().is_empty() => return Err(EMPTY_COMMAND.into()),
+        true => {
+            let mut command = Command::new("sh");
+            command.arg("-c").arg(text);
+            command
+        }
+        false => parse_command(text)?,
+    };
+
+    command.envs(&options.env);
+    if let Some(working_dir) = &options.working_dir {
+        command.current_dir(working_dir);
+    }
     Ok(command)
 }
+
+/// Runs single test cases to the end in tests.
+#[cfg(test)]
+mod testing {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::{RunDirectory, RunnableTestCase};
+    use crate::application::common::{RunConfig, TestCase, TestCaseStatus};
+
+    /// Status and transcript of `test_case`, polled like the runner does.
+    pub fn run(test_case: TestCase, config: RunConfig) -> (TestCaseStatus, Option<String>) {
+        let dir = RunDirectory::new(None, "test").unwrap();
+        let mut test_case = RunnableTestCase::new(test_case, Arc::new(config));
+        test_case.start(dir.path());
+        while !(test_case.has_finished() || test_case.is_over_limit()) {
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        test_case.get_results()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blank_commands_are_rejected() {
+        for shell in [false, true] {
+            let options = CommandOptions { shell, ..CommandOptions::default() };
+            for text in ["", "   ", "\t\n"] {
+                let err = build_command(text, &options).unwrap_err();
+                assert_eq!(err, EMPTY_COMMAND, "{:?} in shell: {}", text, shell);
+            }
+        }
+    }
+
+    #[test]
+    fn unbalanced_quotes_are_reported() {
+        let err = parse_command("echo 'hi").unwrap_err();
+        assert_eq!(err, "Failed to parse command: \"echo 'hi\"");
+    }
+}
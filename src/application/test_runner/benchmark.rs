@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use crate::application::common::{Benchmark, Clock, TestCaseStatus, TimeStats, Timing};
+
+#[derive(Debug, Default)]
+struct Progress {
+    finished_runs: usize,
+    samples: Vec<Timing>,
+    max_rss: u64,
+}
+
+/// Collects the results of the repeated runs of stress tests.
+#[derive(Debug, Default)]
+pub struct Benchmarks {
+    benchmark: Benchmark,
+    clock: Clock,
+    progress: HashMap<usize, Progress>,
+}
+
+impl Benchmarks {
+    pub fn new(benchmark: Benchmark, clock: Clock) -> Self {
+        Self {
+            benchmark: Benchmark {
+                warmup: benchmark.warmup,
+                runs: benchmark.runs.max(1),
+            },
+            clock,
+            progress: HashMap::new(),
+        }
+    }
+
+    /// Records the result of a single run. Returns the final status once the
+    /// test case is done, or `None` if it has to be run again. A run which
+    /// did not pass ends the benchmark right away.
+    pub fn record(&mut self, id: usize, status: TestCaseStatus) -> Option<TestCaseStatus> {
+        let (time, complexity, max_rss) = match status {
+            TestCaseStatus::Pass { time, complexity, max_rss, .. } => (time, complexity, max_rss),
+            status => {
+                self.progress.remove(&id);
+                return Some(status);
+            }
+        };
+
+        let progress = self.progress.entry(id).or_default();
+        progress.finished_runs += 1;
+        if progress.finished_runs > self.benchmark.warmup {
+            progress.samples.push(time);
+            progress.max_rss = progress.max_rss.max(max_rss);
+        }
+        if progress.finished_runs < self.benchmark.warmup + self.benchmark.runs {
+            return None;
+        }
+
+        let Progress { samples, max_rss, .. } = self.progress.remove(&id)?;
+        if samples.len() < 2 {
+            return Some(TestCaseStatus::Pass { time, complexity, max_rss, stats: None });
+        }
+
+        let clock = self.clock;
+        let durations = samples.iter().map(|sample| sample.get(clock)).collect();
+        let stats = TimeStats::from_samples(clock, durations)?;
+        // The run closest to the median represents the whole benchmark
+        let time = samples
+            .iter()
+            .min_by_key(|sample| sample.get(clock).abs_diff(stats.median))
+            .copied()
+            .unwrap_or(time);
+        Some(TestCaseStatus::Pass { time, complexity, max_rss, stats: Some(stats) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn pass(millis: u64) -> TestCaseStatus {
+        let time = Timing { wall: Duration::from_millis(millis), cpu: Duration::ZERO };
+        TestCaseStatus::Pass { time, complexity: 1, max_rss: millis, stats: None }
+    }
+
+    #[test]
+    fn warmup_runs_are_not_measured() {
+        let mut benchmarks = Benchmarks::new(Benchmark { warmup: 1, runs: 3 }, Clock::Wall);
+        assert_eq!(benchmarks.record(0, pass(1000)), None);
+        assert_eq!(benchmarks.record(0, pass(30)), None);
+        assert_eq!(benchmarks.record(0, pass(10)), None);
+        let status = benchmarks.record(0, pass(20)).unwrap();
+
+        let TestCaseStatus::Pass { time, max_rss, stats: Some(stats), .. } = status else {
+            panic!("{:?}", status);
+        };
+        assert_eq!(stats.samples, [30, 10, 20].map(Duration::from_millis));
+        assert_eq!(time.wall, stats.median);
+        assert_eq!(max_rss, 30);
+    }
+
+    #[test]
+    fn failed_run_ends_the_benchmark() {
+        let mut benchmarks = Benchmarks::new(Benchmark { warmup: 0, runs: 3 }, Clock::Wall);
+        assert_eq!(benchmarks.record(0, pass(10)), None);
+        assert_eq!(benchmarks.record(0, TestCaseStatus::Cancelled), Some(TestCaseStatus::Cancelled));
+        assert_eq!(benchmarks.record(0, pass(10)), None);
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+
+/// Remembers the last successful build, so that the solution is not
+/// compiled again if neither the compile command nor its inputs changed.
+#[derive(Debug, Default)]
+pub struct BuildCache {
+    last_build: Option<u64>,
+    pending: Option<u64>,
+}
+
+impl BuildCache {
+    /// Whether a build with this key has already succeeded.
+    pub fn is_up_to_date(&self, key: Option<u64>) -> bool {
+        key.is_some() && key == self.last_build
+    }
+
+    pub fn start(&mut self, key: Option<u64>) {
+        self.last_build = None;
+        self.pending = key;
+    }
+
+    pub fn finish(&mut self, success: bool) {
+        let key = self.pending.take();
+        if success {
+            self.last_build = key;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.last_build = None;
+        self.pending = None;
+    }
+}
+
+/// Hash of the compile command and the contents of every input of the build.
+/// Directories are hashed with all files inside of them. Returns `None` if
+/// there are no inputs to hash or one of them cannot be read.
+pub fn build_key(compile_script: &str, inputs: &[&str]) -> Option<u64> {
+    if inputs.is_empty() {
+        return None;
+    }
+
+    let mut hasher = DefaultHasher::new();
+    compile_script.hash(&mut hasher);
+    for input in inputs {
+        input.hash(&mut hasher);
+        hash_path(Path::new(input), &mut hasher)?;
+    }
+    Some(hasher.finish())
+}
+
+fn hash_path(path: &Path, hasher: &mut DefaultHasher) -> Option<()> {
+    if path.is_dir() {
+        let mut entries: Vec<_> = std::fs::read_dir(path)
+            .ok()?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .collect();
+        entries.sort();
+        for entry in entries {
+            entry.hash(hasher);
+            hash_path(&entry, hasher)?;
+        }
+    } else {
+        std::fs::read(path).ok()?.hash(hasher);
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::RunDirectory;
+    use super::*;
+
+    /// Directory with `main.cpp` and `lib/util.h`.
+    fn sources() -> RunDirectory {
+        let dir = RunDirectory::new(None, "test").unwrap();
+        std::fs::write(dir.path().join("main.cpp"), "int main() {}").unwrap();
+        std::fs::create_dir(dir.path().join("lib")).unwrap();
+        std::fs::write(dir.path().join("lib/util.h"), "#pragma once").unwrap();
+        dir
+    }
+
+    fn key(script: &str, dir: &RunDirectory, inputs: &[&str]) -> Option<u64> {
+        let paths: Vec<String> = inputs.iter().map(|input| dir.path().join(input).to_string_lossy().into_owned()).collect();
+        let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+        build_key(script, &paths)
+    }
+
+    #[test]
+    fn key_changes_with_the_command_and_the_inputs() {
+        let dir = sources();
+        let original = key("g++ main.cpp", &dir, &["main.cpp", "lib"]);
+        assert!(original.is_some());
+        assert_eq!(key("g++ main.cpp", &dir, &["main.cpp", "lib"]), original);
+        assert_ne!(key("g++ -O2 main.cpp", &dir, &["main.cpp", "lib"]), original);
+
+        std::fs::write(dir.path().join("lib/util.h"), "#pragma once\n").unwrap();
+        assert_ne!(key("g++ main.cpp", &dir, &["main.cpp", "lib"]), original);
+    }
+
+    #[test]
+    fn key_needs_readable_inputs() {
+        let dir = sources();
+        assert_eq!(key("g++ main.cpp", &dir, &[]), None);
+        assert_eq!(key("g++ main.cpp", &dir, &["main.cpp", "missing.h"]), None);
+    }
+
+    #[test]
+    fn only_successful_builds_are_reused() {
+        let mut cache = BuildCache::default();
+        assert!(!cache.is_up_to_date(Some(1)));
+
+        cache.start(Some(1));
+        cache.finish(false);
+        assert!(!cache.is_up_to_date(Some(1)));
+
+        cache.start(Some(1));
+        cache.finish(true);
+        assert!(cache.is_up_to_date(Some(1)));
+        assert!(!cache.is_up_to_date(Some(2)));
+        assert!(!cache.is_up_to_date(None));
+
+        cache.start(Some(2));
+        assert!(!cache.is_up_to_date(Some(1)), "a build in progress replaces the last one");
+        cache.finish(true);
+        cache.clear();
+        assert!(!cache.is_up_to_date(Some(2)));
+    }
+}
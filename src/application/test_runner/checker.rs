@@ -0,0 +1,282 @@
+use std::{
+    fs::File,
+    process::{Child, Stdio},
+    time::{Duration, Instant},
+};
+
+use super::{build_command, to_string, TestCaseIO};
+use crate::application::common::{Checker, CommandOptions};
+
+/// Outcome of comparing the output of a solution with the expected one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Accepted,
+    WrongAnswer { message: String },
+}
+
+fn wrong_answer(message: impl Into<String>) -> Verdict {
+    Verdict::WrongAnswer {
+        message: message.into(),
+    }
+}
+
+/// Custom checkers which run longer than this are killed.
+const CUSTOM_CHECKER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Checks `actual` against `expected` with a built-in checker. Custom
+/// checkers are separate programs, which are run with `CustomCheck`.
+pub fn check(checker: &Checker, expected: &str, actual: &str) -> Option<Verdict> {
+    let verdict = match checker {
+        Checker::Exact => check_exact(expected, actual),
+        Checker::Tokens => check_tokens(expected, actual),
+        Checker::Lines => check_lines(expected, actual),
+        Checker::Float { absolute, relative } => check_floats(expected, actual, *absolute, *relative),
+        Checker::UnorderedLines => check_unordered_lines(expected, actual),
+        Checker::Custom { .. } => return None,
+    };
+    Some(verdict)
+}
+
+fn check_exact(expected: &str, actual: &str) -> Verdict {
+    if expected == actual {
+        return Verdict::Accepted;
+    }
+
+    let position = expected
+        .chars()
+        .zip(actual.chars())
+        .take_while(|(e, a)| e == a)
+        .count();
+    wrong_answer(format!("Outputs differ at character {}", position))
+}
+
+fn compare_sequences<'a>(
+    what: &str,
+    expected: impl Iterator<Item = &'a str>,
+    actual: impl Iterator<Item = &'a str>,
+    equal: impl Fn(&str, &str) -> bool,
+) -> Verdict {
+    let expected: Vec<&str> = expected.collect();
+    let actual: Vec<&str> = actual.collect();
+
+    for (n, (e, a)) in expected.iter().zip(actual.iter()).enumerate() {
+        if !equal(e, a) {
+            return wrong_answer(format!("{} {} differs: expected `{}`, found `{}`", what, n + 1, e, a));
+        }
+    }
+
+    if expected.len() != actual.len() {
+        return wrong_answer(format!(
+            "Expected {} {}(s), found {}",
+            expected.len(),
+            what.to_lowercase(),
+            actual.len()
+        ));
+    }
+    Verdict::Accepted
+}
+
+fn check_tokens(expected: &str, actual: &str) -> Verdict {
+    compare_sequences(
+        "Token",
+        expected.split_whitespace(),
+        actual.split_whitespace(),
+        |e, a| e == a,
+    )
+}
+
+/// Lines without trailing whitespace and without trailing empty lines.
+fn normalized_lines(text: &str) -> Vec<&str> {
+    let mut lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+    while lines.last() == Some(&"") {
+        lines.pop();
+    }
+    lines
+}
+
+fn check_lines(expected: &str, actual: &str) -> Verdict {
+    compare_sequences(
+        "Line",
+        normalized_lines(expected).into_iter(),
+        normalized_lines(actual).into_iter(),
+        |e, a| e == a,
+    )
+}
+
+fn check_unordered_lines(expected: &str, actual: &str) -> Verdict {
+    let mut expected = normalized_lines(expected);
+    let mut actual = normalized_lines(actual);
+    expected.sort_unstable();
+    actual.sort_unstable();
+
+    match compare_sequences("Line", expected.into_iter(), actual.into_iter(), |e, a| e == a) {
+        Verdict::Accepted => Verdict::Accepted,
+        Verdict::WrongAnswer { .. } => wrong_answer("Sets of lines differ"),
+    }
+}
+
+/// Tokens which are numbers on both sides are compared with the given
+/// tolerance, the rest must be equal.
+fn check_floats(expected: &str, actual: &str, absolute: f64, relative: f64) -> Verdict {
+    let floats_equal = |e: &str, a: &str| match (e.parse::<f64>(), a.parse::<f64>()) {
+        (Ok(e), Ok(a)) => {
+            let diff = (e - a).abs();
+            diff <= absolute || diff <= relative * e.abs()
+        }
+        _ => e == a,
+    };
+
+    compare_sequences(
+        "Token",
+        expected.split_whitespace(),
+        actual.split_whitespace(),
+        floats_equal,
+    )
+}
+
+/// Custom checker run as `command <input> <expected output> <actual output>`.
+/// Exit code 0 means the answer is accepted, anything printed is used as the
+/// message. It runs in the background, so that the runner can keep polling
+/// the other test cases.
+#[derive(Debug)]
+pub struct CustomCheck {
+    process: Child,
+    start_time: Instant,
+    log_loc: String,
+}
+
+impl CustomCheck {
+    pub fn start(command: &str, options: &CommandOptions, io: &TestCaseIO, expected: &str) -> Result<Self, String> {
+        let answer_path = io.write_answer(expected)?;
+        let log_loc = io.checker_log_path();
+        let log = File::create(&log_loc).map_err(to_string)?;
+        let full_command = format!(
+            "{} {} {} {}",
+            command,
+            shlex::quote(&io.input_path()),
+            shlex::quote(&answer_path),
+            shlex::quote(&io.output_path())
+        );
+
+        let process = build_command(full_command, options)?
+            .stdin(Stdio::null())
+            .stdout(log.try_clone().map_err(to_string)?)
+            .stderr(log)
+            .spawn()
+            .map_err(|err| format!("Failed to run the checker: {}", err))?;
+
+        Ok(Self {
+            process,
+            start_time: Instant::now(),
+            log_loc,
+        })
+    }
+
+    pub fn has_finished(&mut self) -> bool {
+        !matches!(self.process.try_wait(), Ok(None))
+    }
+
+    pub fn is_over_time_limit(&self) -> bool {
+        self.start_time.elapsed() > CUSTOM_CHECKER_TIMEOUT
+    }
+
+    pub fn kill(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+
+    /// Verdict of the finished checker. A checker which is still running is
+    /// killed, as it is only asked once it is over its time limit.
+    pub fn verdict(mut self) -> Result<Verdict, String> {
+        let status = match self.process.try_wait().map_err(to_string)? {
+            Some(status) => status,
+            None => {
+                self.kill();
+                return Err(format!(
+                    "Checker did not finish within {} s",
+                    CUSTOM_CHECKER_TIMEOUT.as_secs()
+                ));
+            }
+        };
+
+        let message = std::fs::read_to_string(&self.log_loc).unwrap_or_default();
+        match status.code() {
+            Some(0) => Ok(Verdict::Accepted),
+            Some(_) if message.trim().is_empty() => Ok(wrong_answer("Rejected by the checker")),
+            Some(_) => Ok(wrong_answer(message.trim_end())),
+            None => Err(format!("Checker was killed: {}", to_string(status))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::RunDirectory;
+    use super::*;
+
+    fn accepted(checker: &Checker, expected: &str, actual: &str) -> bool {
+        check(checker, expected, actual) == Some(Verdict::Accepted)
+    }
+
+    #[test]
+    fn floats_within_the_error_are_accepted() {
+        let checker = Checker::Float { absolute: 1e-6, relative: 1e-9 };
+        assert!(accepted(&checker, "0.5 abc", "0.5000009 abc"));
+        assert!(!accepted(&checker, "0.5 abc", "0.500002 abc"));
+        assert!(!accepted(&checker, "0.5 abc", "0.5 abd"));
+
+        let checker = Checker::Float { absolute: 0.0, relative: 1e-6 };
+        assert!(accepted(&checker, "1000000", "1000000.9"));
+        assert!(!accepted(&checker, "1000000", "1000001.1"));
+    }
+
+    #[test]
+    fn floats_must_have_as_many_tokens() {
+        let checker = Checker::Float { absolute: 1e-6, relative: 0.0 };
+        assert_eq!(check(&checker, "1 2", "1"), Some(wrong_answer("Expected 2 token(s), found 1")));
+    }
+
+    #[test]
+    fn unordered_lines_ignore_the_order() {
+        assert!(accepted(&Checker::UnorderedLines, "a\nb\nc\n", "c\na  \nb"));
+        assert!(!accepted(&Checker::UnorderedLines, "a\nb\n", "a\na\n"));
+        assert!(!accepted(&Checker::UnorderedLines, "a\nb\n", "a\nb\nc\n"));
+    }
+
+    #[test]
+    fn custom_checker_is_left_to_its_program() {
+        assert_eq!(check(&Checker::Custom { command: "true".to_string() }, "1", "2"), None);
+    }
+
+    /// Verdict of `command` as a custom checker, with the solution having
+    /// printed `actual`.
+    fn custom_verdict(command: &str, expected: &str, actual: &str) -> Result<Verdict, String> {
+        let dir = RunDirectory::new(None, "test").unwrap();
+        let io = TestCaseIO::new(dir.path(), 0, "input").unwrap();
+        std::fs::write(io.output_path(), actual).unwrap();
+        let mut check = CustomCheck::start(command, &CommandOptions::default(), &io, expected).unwrap();
+        while !check.has_finished() {
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        check.verdict()
+    }
+
+    #[test]
+    fn custom_checker_exit_code_decides() {
+        let compare = "sh -c 'cmp -s \"$2\" \"$3\"' checker";
+        assert_eq!(custom_verdict(compare, "42\n", "42\n"), Ok(Verdict::Accepted));
+        assert_eq!(custom_verdict(compare, "42\n", "41\n"), Ok(wrong_answer("Rejected by the checker")));
+    }
+
+    #[test]
+    fn custom_checker_message_is_shown() {
+        let reject = "sh -c 'echo \"off by one\"; exit 3'";
+        assert_eq!(custom_verdict(reject, "", ""), Ok(wrong_answer("off by one")));
+    }
+
+    #[test]
+    fn killed_custom_checker_is_an_error() {
+        let verdict = custom_verdict("sh -c 'kill -9 $$'", "", "");
+        assert!(verdict.is_err(), "{:?}", verdict);
+    }
+}
@@ -4,11 +4,11 @@ use crate::application::test_runner::RemoteRunner;
 use crate::application::RunDetails;
 use crate::application::RunRequest;
 use crate::application::RunResponse;
-use crate::application::TestCase;
-use std::sync::mpsc::SendError;
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::time::Duration;
 
+const EXIT_TIMEOUT: Duration = Duration::from_secs(1);
+
 #[derive(Debug)]
 pub struct CodeRunner {
     handle: std::thread::JoinHandle<std::result::Result<(), String>>,
@@ -16,17 +16,10 @@ pub struct CodeRunner {
     outgoing: Sender<RunRequest>,
 }
 impl CodeRunner {
-    pub fn please_run(
-        &self,
-        test_cases: Vec<TestCase>,
-        compile_script: String,
-        run_script: String,
-    ) -> Result<(), SendError<RunRequest>> {
-        self.outgoing.send(RunRequest::PleaseRun(RunDetails {
-            compile_script,
-            run_script,
-            test_cases,
-        }))
+    pub fn please_run(&self, run_details: RunDetails) -> Result<(), String> {
+        self.outgoing
+            .send(RunRequest::PleaseRun(Box::new(run_details)))
+            .map_err(to_string)
     }
 
     fn check_thread(&self) -> Result<(), String> {
@@ -42,10 +35,19 @@ impl CodeRunner {
             .map_err(to_string)
     }
 
+    /// Stops the runner thread and waits until it has cleaned up after itself.
+    pub fn please_exit(&self) -> Result<(), String> {
+        self.outgoing
+            .send(RunRequest::PleaseExit)
+            .map_err(to_string)?;
+        while self.incoming.recv_timeout(EXIT_TIMEOUT).is_ok() {}
+        Ok(())
+    }
+
     pub fn get_updates(&self) -> Vec<RunResponse> {
         if let Err(err_msg) = self.check_thread() {
             let status = TestCaseStatus::Err { err_msg };
-            return vec![RunResponse { id: 0, status }];
+            return vec![RunResponse::TestCase { id: 0, status }];
         }
 
         let mut updates = Vec::new();
@@ -0,0 +1,75 @@
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    process::{Child, Stdio},
+};
+
+use super::{build_command, to_string};
+use crate::application::common::{CommandOptions, CompilationStatus};
+
+/// Compiler process running in the background. Its output is captured into
+/// the directory of the run.
+#[derive(Debug)]
+pub struct Compilation {
+    process: Child,
+    stdout_loc: PathBuf,
+    stderr_loc: PathBuf,
+}
+
+impl Compilation {
+    pub fn start(command: String, options: &CommandOptions, dir: &Path) -> Result<Self, String> {
+        let stdout_loc = dir.join("compile.out");
+        let stderr_loc = dir.join("compile.err");
+        let stdout = File::create(&stdout_loc).map_err(to_string)?;
+        let stderr = File::create(&stderr_loc).map_err(to_string)?;
+
+        let process = build_command(command, options)?
+            .stdin(Stdio::null())
+            .stdout(stdout)
+            .stderr(stderr)
+            .spawn()
+            .map_err(to_string)?;
+
+        Ok(Self {
+            process,
+            stdout_loc,
+            stderr_loc,
+        })
+    }
+
+    /// Returns `None` while the compiler is still running.
+    pub fn try_finish(&mut self) -> Option<CompilationStatus> {
+        let exit_status = match self.process.try_wait() {
+            Ok(Some(exit_status)) => exit_status,
+            Ok(None) => return None,
+            Err(err) => {
+                let diagnostics = format!("Error checking exit status of the compiler: {}", err);
+                return Some(CompilationStatus::CompilationError { diagnostics });
+            }
+        };
+
+        if exit_status.success() {
+            return Some(CompilationStatus::Compiled);
+        }
+
+        let diagnostics = self.diagnostics(&format!("Compiler exited with {}", exit_status));
+        Some(CompilationStatus::CompilationError { diagnostics })
+    }
+
+    /// Everything the compiler printed, followed by `summary`.
+    fn diagnostics(&self, summary: &str) -> String {
+        let stdout = std::fs::read_to_string(&self.stdout_loc).unwrap_or_default();
+        let stderr = std::fs::read_to_string(&self.stderr_loc).unwrap_or_default();
+
+        [stdout.trim_end(), stderr.trim_end(), summary]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn kill(mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
@@ -0,0 +1,182 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::RunnableTestCase;
+use crate::application::common::{
+    Checker, DifferentialTest, InputMode, Limits, RunConfig, TestCase, TestCaseStatus,
+};
+
+/// Helpers may run this many times longer than the solution.
+const HELPER_TIME_FACTOR: u32 = 10;
+/// Time limit of helpers when the problem has none.
+const HELPER_TIME_LIMIT: Duration = Duration::from_secs(10);
+
+/// Outcome of checking on a differential test.
+#[derive(Debug)]
+pub enum Progress {
+    Running,
+    /// The solution passed on another input
+    Passed(usize),
+    /// The solution failed on this test case
+    Found(Box<(TestCase, TestCaseStatus)>),
+    Failed(String),
+}
+
+#[derive(Debug)]
+enum Step {
+    Generate(RunnableTestCase),
+    BruteForce(String, RunnableTestCase),
+    Solution(TestCase, RunnableTestCase),
+}
+
+impl Step {
+    fn process(&mut self) -> &mut RunnableTestCase {
+        match self {
+            Step::Generate(process) | Step::BruteForce(_, process) | Step::Solution(_, process) => process,
+        }
+    }
+
+    fn kill(self) {
+        match self {
+            Step::Generate(process) | Step::BruteForce(_, process) | Step::Solution(_, process) => process.kill(),
+        }
+    }
+}
+
+/// Looks for an input on which the solution fails: generates an input,
+/// runs the brute force solution on it for the expected output, then runs
+/// the solution and judges it, until it does not pass.
+#[derive(Debug)]
+pub struct Differential {
+    generator: Arc<RunConfig>,
+    brute_force: Arc<RunConfig>,
+    solution: Arc<RunConfig>,
+    id: usize,
+    passed: usize,
+    step: Option<Step>,
+}
+
+impl Differential {
+    /// `solution` is how the tested solution is run.
+    pub fn new(test: DifferentialTest, solution: Arc<RunConfig>) -> Self {
+        Self {
+            generator: helper_config(test.generator, InputMode::Argv, &solution),
+            brute_force: helper_config(test.brute_force, solution.input_mode, &solution),
+            solution,
+            id: test.id,
+            passed: 0,
+            step: None,
+        }
+    }
+
+    pub fn passed(&self) -> usize {
+        self.passed
+    }
+
+    /// Moves on to the next step if the current one has finished.
+    pub fn poll(&mut self, dir: &Path) -> Progress {
+        let step = match &mut self.step {
+            Some(step) => step,
+            None => {
+                self.step = Some(self.generate(dir));
+                return Progress::Running;
+            }
+        };
+
+        let process = step.process();
+        if !(process.has_finished() || process.is_over_limit()) {
+            return Progress::Running;
+        }
+
+        match self.step.take() {
+            Some(Step::Generate(generator)) => match generator.into_output() {
+                Ok(input) => {
+                    let brute_force = start_helper(&self.brute_force, self.id, input.clone(), dir);
+                    self.step = Some(Step::BruteForce(input, brute_force));
+                    Progress::Running
+                }
+                Err(err_msg) => Progress::Failed(format!("Generator failed on seed {}: {}", self.seed(), err_msg)),
+            },
+            Some(Step::BruteForce(input, brute_force)) => match brute_force.into_output() {
+                Ok(output) => {
+                    let test_case = TestCase {
+                        id: self.id,
+                        complexity: input.len() as u32,
+                        input,
+                        output,
+                        is_stress_test: false,
+                        time_limit_ms: None,
+                        reference: None,
+                    };
+                    let mut solution = RunnableTestCase::new(test_case.clone(), Arc::clone(&self.solution));
+                    solution.start(dir);
+                    self.step = Some(Step::Solution(test_case, solution));
+                    Progress::Running
+                }
+                Err(err_msg) => Progress::Failed(format!("Brute force failed on seed {}: {}", self.seed(), err_msg)),
+            },
+            Some(Step::Solution(test_case, solution)) => match solution.get_results().0 {
+                TestCaseStatus::Pass { .. } => {
+                    self.passed += 1;
+                    self.step = Some(self.generate(dir));
+                    Progress::Passed(self.passed)
+                }
+                status => Progress::Found(Box::new((test_case, status))),
+            },
+            None => Progress::Running,
+        }
+    }
+
+    pub fn kill(self) {
+        if let Some(step) = self.step {
+            step.kill();
+        }
+    }
+
+    /// Seed of the current input, which the generator gets as its argument.
+    fn seed(&self) -> usize {
+        self.passed + 1
+    }
+
+    fn generate(&self, dir: &Path) -> Step {
+        Step::Generate(start_helper(&self.generator, self.id, self.seed().to_string(), dir))
+    }
+}
+
+/// How a helper program, like a generator or a brute force solution, is
+/// run next to the solution. It is started the same way, with a generous
+/// time limit and no memory limit.
+pub fn helper_config(run_script: String, input_mode: InputMode, solution: &RunConfig) -> Arc<RunConfig> {
+    Arc::new(RunConfig {
+        run_script,
+        run_options: solution.run_options.clone(),
+        input_mode,
+        limits: Limits {
+            time: Some(solution.limits.time.map_or(HELPER_TIME_LIMIT, |limit| limit * HELPER_TIME_FACTOR)),
+            output: solution.limits.output,
+            ..Limits::default()
+        },
+        checker: Checker::default(),
+        interactor: None,
+        sandbox: false,
+        clock: solution.clock,
+    })
+}
+
+/// Starts a helper program on `input`, its output is read with
+/// `RunnableTestCase::into_output`.
+pub fn start_helper(config: &Arc<RunConfig>, id: usize, input: String, dir: &Path) -> RunnableTestCase {
+    let test_case = TestCase {
+        id,
+        complexity: 0,
+        input,
+        output: String::new(),
+        is_stress_test: false,
+        time_limit_ms: None,
+        reference: None,
+    };
+    let mut process = RunnableTestCase::new(test_case, Arc::clone(config));
+    process.start(dir);
+    process
+}
@@ -0,0 +1,195 @@
+use std::{
+    fs::File,
+    io::{Read, Write},
+    process::{Child, ExitStatus, Stdio},
+    sync::{Arc, Mutex},
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use super::{build_command, to_string, TestCaseIO};
+use crate::application::common::CommandOptions;
+
+/// Transcripts longer than this are truncated
+const MAX_TRANSCRIPT_LEN: usize = 64 * 1024;
+const SOLUTION_PREFIX: &str = "solution   > ";
+const INTERACTOR_PREFIX: &str = "interactor > ";
+
+#[derive(Debug, Default)]
+struct Transcript {
+    text: String,
+    truncated: bool,
+}
+
+impl Transcript {
+    fn record(&mut self, prefix: &str, line: &[u8]) {
+        if self.truncated {
+            return;
+        }
+
+        for line in String::from_utf8_lossy(line).lines() {
+            if self.text.len() + prefix.len() + line.len() > MAX_TRANSCRIPT_LEN {
+                self.truncated = true;
+                self.text.push_str("... (transcript truncated)\n");
+                return;
+            }
+            self.text.push_str(prefix);
+            self.text.push_str(line);
+            self.text.push('\n');
+        }
+    }
+}
+
+/// Copies everything from `from` to `to`, writing it down in the transcript
+/// line by line. Dropping `to` at the end lets the other side know that there
+/// is no more input.
+fn relay(
+    mut from: impl Read,
+    mut to: impl Write,
+    prefix: &'static str,
+    transcript: Arc<Mutex<Transcript>>,
+) {
+    let record = |line: &[u8]| {
+        if let Ok(mut transcript) = transcript.lock() {
+            transcript.record(prefix, line);
+        }
+    };
+
+    let mut buf = [0u8; 4096];
+    let mut pending_line = Vec::new();
+    loop {
+        let n = match from.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+
+        pending_line.extend_from_slice(&buf[..n]);
+        if let Some(end) = pending_line.iter().rposition(|&b| b == b'\n') {
+            record(&pending_line[..end]);
+            pending_line.drain(..=end);
+        }
+
+        if to.write_all(&buf[..n]).and_then(|_| to.flush()).is_err() {
+            break;
+        }
+    }
+
+    if !pending_line.is_empty() {
+        record(&pending_line);
+    }
+}
+
+/// Interactor process connected to the standard input and output of the
+/// solution. The interactor decides whether the solution is correct.
+#[derive(Debug)]
+pub struct Interaction {
+    interactor: Child,
+    start_time: Instant,
+    /// Wall time the interactor may take
+    time_limit: Duration,
+    exited: bool,
+    relays: Vec<JoinHandle<()>>,
+    transcript: Arc<Mutex<Transcript>>,
+    log_loc: String,
+}
+
+impl Interaction {
+    /// Runs `command <input> <expected output>` and connects it with the
+    /// solution, which must have been spawned with piped stdin and stdout.
+    pub fn start(
+        command: &str,
+        options: &CommandOptions,
+        io: &TestCaseIO,
+        expected: &str,
+        solution: &mut Child,
+        time_limit: Duration,
+    ) -> Result<Self, String> {
+        let answer_path = io.write_answer(expected)?;
+        let log_loc = io.interactor_log_path();
+        let log = File::create(&log_loc).map_err(to_string)?;
+
+        let full_command = format!(
+            "{} {} {}",
+            command,
+            shlex::quote(&io.input_path()),
+            shlex::quote(&answer_path),
+        );
+        let mut interactor = build_command(full_command, options)?
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(log)
+            .spawn()
+            .map_err(|err| format!("Failed to start the interactor: {}", err))?;
+
+        let missing_pipe = || "Solution and interactor must be connected with pipes".to_string();
+        let solution_stdin = solution.stdin.take().ok_or_else(missing_pipe)?;
+        let solution_stdout = solution.stdout.take().ok_or_else(missing_pipe)?;
+        let interactor_stdin = interactor.stdin.take().ok_or_else(missing_pipe)?;
+        let interactor_stdout = interactor.stdout.take().ok_or_else(missing_pipe)?;
+
+        let transcript = Arc::new(Mutex::new(Transcript::default()));
+        let to_interactor = {
+            let transcript = Arc::clone(&transcript);
+            std::thread::spawn(move || {
+                relay(solution_stdout, interactor_stdin, SOLUTION_PREFIX, transcript)
+            })
+        };
+        let to_solution = {
+            let transcript = Arc::clone(&transcript);
+            std::thread::spawn(move || {
+                relay(interactor_stdout, solution_stdin, INTERACTOR_PREFIX, transcript)
+            })
+        };
+
+        Ok(Self {
+            interactor,
+            start_time: Instant::now(),
+            time_limit,
+            exited: false,
+            relays: vec![to_interactor, to_solution],
+            transcript,
+            log_loc,
+        })
+    }
+
+    pub fn has_finished(&mut self) -> bool {
+        self.exited = self.exited || !matches!(self.interactor.try_wait(), Ok(None));
+        self.exited
+    }
+
+    /// Whether the interactor is still running past its time limit, for
+    /// example waiting on a solution which has already exited.
+    pub fn is_over_time_limit(&self) -> bool {
+        !self.exited && self.start_time.elapsed() > self.time_limit
+    }
+
+    pub fn time_limit(&self) -> Duration {
+        self.time_limit
+    }
+
+    /// Waits for the interactor and returns its exit status together with
+    /// everything it wrote to stderr.
+    pub fn finish(&mut self) -> Result<(ExitStatus, String), String> {
+        let status = self.interactor.wait().map_err(to_string)?;
+        for relay in self.relays.drain(..) {
+            let _ = relay.join();
+        }
+        let message = std::fs::read_to_string(&self.log_loc).unwrap_or_default();
+        Ok((status, message.trim_end().to_string()))
+    }
+
+    pub fn transcript(&self) -> String {
+        self.transcript
+            .lock()
+            .map(|transcript| transcript.text.clone())
+            .unwrap_or_default()
+    }
+
+    /// Stops the interactor without joining the relays, which finish on
+    /// their own once the solution is gone too.
+    pub fn kill(&mut self) {
+        let _ = self.interactor.kill();
+        let _ = self.interactor.wait();
+        self.relays.clear();
+    }
+}
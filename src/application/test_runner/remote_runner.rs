@@ -1,20 +1,40 @@
 use super::to_string;
-use crate::application::test_runner::parse_command;
+use crate::application::common::{CompilationStatus, DifferentialStatus, ShrinkStatus};
+use crate::application::test_runner::build_cache::build_key;
+use crate::application::test_runner::Benchmarks;
+use crate::application::test_runner::BuildCache;
+use crate::application::test_runner::Compilation;
+use crate::application::test_runner::{Differential, Progress};
+use crate::application::test_runner::RunDirectory;
 use crate::application::test_runner::RunnableTestCase;
+use crate::application::test_runner::{ShrinkProgress, Shrinker};
 use crate::application::RunDetails;
 use crate::application::RunRequest;
 use crate::application::RunResponse;
 use crate::application::TestCaseStatus;
 use std::collections::VecDeque;
-use std::sync::mpsc::{Receiver, Sender};
+use std::sync::Arc;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender, TryRecvError};
 use std::time::Duration;
 
+/// How long to wait for a new request when there is nothing to run
 const LOOP_WAIT: Duration = Duration::from_millis(50);
+/// How often running test cases are checked
+const POLL_WAIT: Duration = Duration::from_millis(2);
 
 pub struct RemoteRunner {
     incoming: Receiver<RunRequest>,
     outgoing: Sender<RunResponse>,
     to_run: VecDeque<RunnableTestCase>,
+    running: Vec<RunnableTestCase>,
+    jobs: usize,
+    build_dir: Option<RunDirectory>,
+    run_dir: Option<RunDirectory>,
+    compilation: Option<Compilation>,
+    differential: Option<Differential>,
+    shrinker: Option<Shrinker>,
+    benchmarks: Benchmarks,
+    build_cache: BuildCache,
 }
 
 impl RemoteRunner {
@@ -23,95 +43,335 @@ impl RemoteRunner {
             incoming,
             outgoing,
             to_run: VecDeque::new(),
+            running: Vec::new(),
+            jobs: 1,
+            build_dir: None,
+            run_dir: None,
+            compilation: None,
+            differential: None,
+            shrinker: None,
+            benchmarks: Benchmarks::default(),
+            build_cache: BuildCache::default(),
         }
     }
 
     pub fn run(&mut self) -> Result<(), String> {
-        loop {
-            std::thread::sleep(LOOP_WAIT);
-            self.try_handle_next_request()?;
+        while self.try_handle_next_request()? {
             self.continue_running_last_request()?;
         }
+        Ok(())
+    }
+
+    fn is_idle(&self) -> bool {
+        self.compilation.is_none()
+            && self.differential.is_none()
+            && self.shrinker.is_none()
+            && self.to_run.is_empty()
+            && self.running.is_empty()
     }
 
-    fn try_handle_next_request(&mut self) -> Result<(), String> {
-        let new_request = match self.incoming.try_recv().ok() {
+    /// Blocks for a while if there is nothing to do, otherwise just checks
+    /// whether a request has arrived.
+    fn next_request(&mut self) -> Result<Option<RunRequest>, String> {
+        let disconnected = if self.is_idle() {
+            match self.incoming.recv_timeout(LOOP_WAIT) {
+                Ok(req) => return Ok(Some(req)),
+                Err(err) => err == RecvTimeoutError::Disconnected,
+            }
+        } else {
+            std::thread::sleep(POLL_WAIT);
+            match self.incoming.try_recv() {
+                Ok(req) => return Ok(Some(req)),
+                Err(err) => err == TryRecvError::Disconnected,
+            }
+        };
+
+        if disconnected {
+            self.abort_curr_run()?;
+            return Err("Main thread has stopped listening".into());
+        }
+        Ok(None)
+    }
+
+    /// Returns false if the runner should exit.
+    fn try_handle_next_request(&mut self) -> Result<bool, String> {
+        let new_request = match self.next_request()? {
             Some(req) => req,
-            None => return Ok(()),
+            None => return Ok(true),
         };
 
         match new_request {
             RunRequest::PleaseRun(run_details) => {
                 self.abort_curr_run()?;
-                let compilation_status = self.setup_new_run(run_details);
-                if let Err(err_msg) = compilation_status {
-                    let status = TestCaseStatus::Err { err_msg };
-                    self.notify(0, status)?;
+                if let Err(diagnostics) = self.setup_new_run(*run_details) {
+                    self.to_run.clear();
+                    self.cancel_searches()?;
+                    let status = CompilationStatus::CompilationError { diagnostics };
+                    self.notify_compilation(status)?;
                 }
             }
             RunRequest::PleaseStop => {
                 self.abort_curr_run()?;
             }
+            RunRequest::PleaseExit => {
+                self.abort_curr_run()?;
+                self.run_dir = None;
+                self.build_dir = None;
+                self.build_cache.clear();
+                return Ok(false);
+            }
         };
 
-        Ok(())
+        Ok(true)
     }
 
     fn notify(&self, id: usize, status: TestCaseStatus) -> Result<(), String> {
-        let response = RunResponse { id, status };
+        let response = RunResponse::TestCase { id, status };
+        self.outgoing.send(response).map_err(to_string)
+    }
+
+    fn notify_compilation(&self, status: CompilationStatus) -> Result<(), String> {
+        let response = RunResponse::Compilation(status);
         self.outgoing.send(response).map_err(to_string)
     }
 
     fn continue_running_last_request(&mut self) -> Result<(), String> {
-        let mut current_test_case: RunnableTestCase = match self.to_run.pop_front() {
-            Some(tc) => tc,
-            None => return Ok(()),
+        if !self.continue_compilation()? {
+            return Ok(());
+        }
+        self.start_next_test_cases()?;
+        self.collect_finished_test_cases()?;
+        self.continue_differential()?;
+        self.continue_shrinking()
+    }
+
+    fn continue_shrinking(&mut self) -> Result<(), String> {
+        let (shrinker, run_dir) = match (&mut self.shrinker, &self.run_dir) {
+            (Some(shrinker), Some(run_dir)) => (shrinker, run_dir),
+            _ => return Ok(()),
+        };
+
+        let original = shrinker.original_id();
+        let status = match shrinker.poll(run_dir.path()) {
+            ShrinkProgress::Running => return Ok(()),
+            ShrinkProgress::Tried { size, tries } => ShrinkStatus::Running { original, size, tries },
+            ShrinkProgress::Done(Some(result)) => {
+                self.shrinker = None;
+                let (test_case, status) = *result;
+                let response = RunResponse::NewTestCase { test_case, status };
+                return self.outgoing.send(response).map_err(to_string);
+            }
+            ShrinkProgress::Done(None) => {
+                let size = shrinker.original_size();
+                self.shrinker = None;
+                ShrinkStatus::Done { original, size, id: None }
+            }
         };
+        self.notify_shrink(status)
+    }
+
+    fn notify_shrink(&self, status: ShrinkStatus) -> Result<(), String> {
+        let response = RunResponse::Shrink(status);
+        self.outgoing.send(response).map_err(to_string)
+    }
 
-        if !current_test_case.has_started() {
-            current_test_case.start();
-            self.notify(current_test_case.id, TestCaseStatus::Running)?;
+    /// Stops looking for failing inputs, by differential testing or by
+    /// shrinking.
+    fn cancel_searches(&mut self) -> Result<(), String> {
+        if let Some(differential) = self.differential.take() {
+            let passed = differential.passed();
+            differential.kill();
+            self.notify_differential(DifferentialStatus::Cancelled { passed })?;
         }
+        if let Some(shrinker) = self.shrinker.take() {
+            shrinker.kill();
+            self.notify_shrink(ShrinkStatus::Cancelled)?;
+        }
+        Ok(())
+    }
 
-        if current_test_case.has_finished() {
-            let id: usize = current_test_case.id;
-            let status = current_test_case.get_results();
-            self.notify(id, status)?;
-        } else {
-            self.to_run.push_front(current_test_case);
+    fn continue_differential(&mut self) -> Result<(), String> {
+        let (differential, run_dir) = match (&mut self.differential, &self.run_dir) {
+            (Some(differential), Some(run_dir)) => (differential, run_dir),
+            _ => return Ok(()),
+        };
+
+        let status = match differential.poll(run_dir.path()) {
+            Progress::Running => return Ok(()),
+            Progress::Passed(passed) => DifferentialStatus::Running { passed },
+            Progress::Found(found) => {
+                self.differential = None;
+                let (test_case, status) = *found;
+                let response = RunResponse::NewTestCase { test_case, status };
+                return self.outgoing.send(response).map_err(to_string);
+            }
+            Progress::Failed(err_msg) => {
+                let passed = differential.passed();
+                self.differential = None;
+                DifferentialStatus::Failed { passed, err_msg }
+            }
+        };
+        self.notify_differential(status)
+    }
+
+    fn notify_differential(&self, status: DifferentialStatus) -> Result<(), String> {
+        let response = RunResponse::Differential(status);
+        self.outgoing.send(response).map_err(to_string)
+    }
+
+    /// Returns true once the solution is compiled and test cases can be run.
+    fn continue_compilation(&mut self) -> Result<bool, String> {
+        let compilation = match &mut self.compilation {
+            Some(compilation) => compilation,
+            None => return Ok(true),
+        };
+
+        let status = match compilation.try_finish() {
+            Some(status) => status,
+            None => return Ok(false),
+        };
+
+        self.compilation = None;
+        self.build_cache.finish(status == CompilationStatus::Compiled);
+        if status != CompilationStatus::Compiled {
+            self.to_run.clear();
+            self.cancel_searches()?;
+        }
+        self.notify_compilation(status)?;
+        Ok(true)
+    }
+
+    /// Stress tests are run one at a time, so that their timings are not
+    /// affected by other test cases. The rest runs on up to `jobs` processes.
+    fn can_start(&self, test_case: &RunnableTestCase) -> bool {
+        if test_case.is_stress_test {
+            return self.running.is_empty();
+        }
+        let stress_test_running = self.running.iter().any(|tc| tc.is_stress_test);
+        !stress_test_running && self.running.len() < self.jobs
+    }
+
+    fn start_next_test_cases(&mut self) -> Result<(), String> {
+        while let Some(next) = self.to_run.front() {
+            if !self.can_start(next) {
+                break;
+            }
+
+            let mut test_case = self.to_run.pop_front().unwrap();
+            match &self.run_dir {
+                Some(run_dir) => test_case.start(run_dir.path()),
+                None => test_case.fail_to_start("Directory for the test case IO is missing"),
+            }
+            self.notify(test_case.id, TestCaseStatus::Running)?;
+            self.running.push(test_case);
         }
+        Ok(())
+    }
 
+    fn collect_finished_test_cases(&mut self) -> Result<(), String> {
+        let mut still_running = Vec::with_capacity(self.running.len());
+        for mut test_case in std::mem::take(&mut self.running) {
+            if test_case.has_finished() || test_case.is_over_limit() {
+                let id: usize = test_case.id;
+                let next_run = test_case.is_stress_test.then(|| test_case.again());
+                let (status, transcript) = test_case.get_results();
+                let status = match next_run {
+                    Some(next_run) => match self.benchmarks.record(id, status) {
+                        Some(status) => status,
+                        None => {
+                            self.to_run.push_front(next_run);
+                            continue;
+                        }
+                    },
+                    None => status,
+                };
+                self.notify(id, status)?;
+                if let Some(transcript) = transcript {
+                    let response = RunResponse::Transcript { id, transcript };
+                    self.outgoing.send(response).map_err(to_string)?;
+                }
+            } else {
+                still_running.push(test_case);
+            }
+        }
+        self.running = still_running;
         Ok(())
     }
 
     fn abort_curr_run(&mut self) -> Result<(), String> {
-        let old_test_cases = std::mem::take(&mut self.to_run);
-        for test_case in old_test_cases.into_iter() {
+        if let Some(compilation) = self.compilation.take() {
+            compilation.kill();
+            self.build_cache.finish(false);
+            self.notify_compilation(CompilationStatus::Cancelled)?;
+        }
+
+        self.cancel_searches()?;
+
+        let old_test_cases = std::mem::take(&mut self.running)
+            .into_iter()
+            .chain(std::mem::take(&mut self.to_run));
+        for test_case in old_test_cases {
             self.notify(test_case.id, TestCaseStatus::Cancelled)?;
             test_case.kill();
         }
         Ok(())
     }
 
-    fn setup_new_run(&mut self, run_details: RunDetails) -> Result<String, String> {
+    fn setup_new_run(&mut self, run_details: RunDetails) -> Result<(), String> {
         let RunDetails {
             compile_script,
-            run_script,
+            compile_options,
+            mut config,
+            source,
+            compile_inputs,
+            jobs,
+            benchmark,
+            artifacts_dir,
             test_cases,
+            differential,
+            shrink,
         } = run_details;
+        self.jobs = jobs.max(1);
+        self.benchmarks = Benchmarks::new(benchmark, config.clock);
+
+        // The solution is compiled into a directory of its own, which is
+        // reused together with the build. The inputs and outputs of the test
+        // cases go to a fresh directory on every run.
+        let inputs: Vec<&str> = source.iter().chain(compile_inputs.iter()).map(String::as_str).collect();
+        let key = build_key(&format!("{:?} {:?} {}", artifacts_dir, compile_options, compile_script), &inputs);
+        let up_to_date = self.build_cache.is_up_to_date(key) && self.build_dir.is_some();
+        if !up_to_date {
+            self.build_dir = None;
+            self.build_dir = Some(RunDirectory::new(artifacts_dir.as_deref(), "build")?);
+        }
+        self.run_dir = None;
+        self.run_dir = Some(RunDirectory::new(artifacts_dir.as_deref(), "run")?);
+        let build_dir = self.build_dir.as_ref().ok_or("Directory of the build is missing")?;
+
+        let compile_script = build_dir.expand(&compile_script, source.as_deref())?;
+        config.run_script = build_dir.expand(&config.run_script, source.as_deref())?;
+
+        let config = Arc::new(config);
+        self.differential = match differential {
+            Some(mut test) => {
+                test.generator = build_dir.expand(&test.generator, source.as_deref())?;
+                test.brute_force = build_dir.expand(&test.brute_force, source.as_deref())?;
+                Some(Differential::new(test, Arc::clone(&config)))
+            }
+            None => None,
+        };
+        self.shrinker = shrink.map(|test| Shrinker::new(test, Arc::clone(&config)));
         self.to_run = test_cases
             .into_iter()
-            .map(|tc| RunnableTestCase::new(tc.id, tc.complexity, run_script.clone(), tc.input, tc.output))
+            .map(|tc| RunnableTestCase::new(tc, Arc::clone(&config)))
             .collect();
 
-        self.compile(compile_script)
-    }
-
-    fn compile(&self, command: String) -> Result<String, String> {
-        let mut process = parse_command(command)?;
-        match process.output() {
-            Ok(_) => Ok("Compilation success!".to_string()),
-            Err(e) => Err(format!("{}", e)),
+        if up_to_date {
+            return self.notify_compilation(CompilationStatus::UpToDate);
         }
+
+        self.build_cache.start(key);
+        self.compilation = Some(Compilation::start(compile_script, &compile_options, build_dir.path())?);
+        self.notify_compilation(CompilationStatus::Compiling)
     }
 }
@@ -0,0 +1,274 @@
+use std::io::{self, PipeReader, PipeWriter, Read, Write};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::process::{Child, Command, ExitStatus};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Resources used by a finished process, as reported by the kernel.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ResourceUsage {
+    /// Peak resident set size in bytes
+    pub max_rss: u64,
+    /// User and system CPU time
+    pub cpu_time: Duration,
+}
+
+fn to_duration(time: libc::timeval) -> Duration {
+    Duration::from_secs(time.tv_sec.max(0) as u64) + Duration::from_micros(time.tv_usec.max(0) as u64)
+}
+
+impl From<&libc::rusage> for ResourceUsage {
+    fn from(rusage: &libc::rusage) -> Self {
+        // Linux reports `ru_maxrss` in kilobytes
+        Self {
+            max_rss: rusage.ru_maxrss.max(0) as u64 * 1024,
+            cpu_time: to_duration(rusage.ru_utime) + to_duration(rusage.ru_stime),
+        }
+    }
+}
+
+fn wait4(child: &Child, options: libc::c_int) -> io::Result<Option<(ExitStatus, ResourceUsage)>> {
+    let pid = child.id() as libc::pid_t;
+    let mut status: libc::c_int = 0;
+    let mut rusage: libc::rusage = unsafe { std::mem::zeroed() };
+
+    loop {
+        let res = unsafe { libc::wait4(pid, &mut status, options, &mut rusage) };
+        match res {
+            0 => return Ok(None),
+            -1 if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted => continue,
+            -1 => return Err(io::Error::last_os_error()),
+            _ => return Ok(Some((ExitStatus::from_raw(status), ResourceUsage::from(&rusage)))),
+        }
+    }
+}
+
+/// Like `Child::try_wait`, but also collects the resource usage of the child.
+/// Once this returns `Some`, the child has been reaped.
+pub fn try_wait(child: &Child) -> io::Result<Option<(ExitStatus, ResourceUsage)>> {
+    wait4(child, libc::WNOHANG)
+}
+
+/// Like `Child::wait`, but also collects the resource usage of the child.
+pub fn wait(child: &Child) -> io::Result<(ExitStatus, ResourceUsage)> {
+    wait4(child, 0)?.ok_or_else(|| io::Error::from(io::ErrorKind::WouldBlock))
+}
+
+/// Notes the moment a child exits, so that its running time does not depend
+/// on how often the runner checks on it. The child is not reaped, so it can
+/// still be waited for and killed safely. Without pidfds (Linux before 5.3)
+/// the exit is only noticed once the child is waited for.
+#[derive(Debug)]
+pub struct ExitWatcher {
+    exited_at: Arc<Mutex<Option<Instant>>>,
+}
+
+impl ExitWatcher {
+    pub fn watch(child: &Child) -> Self {
+        let exited_at = Arc::new(Mutex::new(None));
+        if let (Some(watchers), Ok(pidfd)) = (watchers(), pidfd_open(child)) {
+            watchers.add(Watched { pidfd, exited_at: Arc::clone(&exited_at) });
+        }
+        Self { exited_at }
+    }
+
+    pub fn exited_at(&self) -> Option<Instant> {
+        self.exited_at.lock().ok().and_then(|exited_at| *exited_at)
+    }
+}
+
+#[derive(Debug)]
+struct Watched {
+    /// Becomes readable once the child exits
+    pidfd: OwnedFd,
+    exited_at: Arc<Mutex<Option<Instant>>>,
+}
+
+/// Single thread which watches every child, so that running many test
+/// cases does not take a thread each.
+#[derive(Debug)]
+struct Watchers {
+    pending: Arc<Mutex<Vec<Watched>>>,
+    /// Wakes the thread up to watch the pending children too
+    wake: PipeWriter,
+}
+
+impl Watchers {
+    fn start() -> io::Result<Self> {
+        let (wake_up, wake) = io::pipe()?;
+        let pending = Arc::new(Mutex::new(Vec::new()));
+        let thread_pending = Arc::clone(&pending);
+        std::thread::Builder::new()
+            .name("exit-watcher".to_string())
+            .spawn(move || watch_exits(wake_up, thread_pending))?;
+        Ok(Self { pending, wake })
+    }
+
+    fn add(&self, watched: Watched) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.push(watched);
+        }
+        let _ = (&self.wake).write(&[0]);
+    }
+}
+
+fn watchers() -> Option<&'static Watchers> {
+    static WATCHERS: OnceLock<Option<Watchers>> = OnceLock::new();
+    WATCHERS.get_or_init(|| Watchers::start().ok()).as_ref()
+}
+
+fn pidfd_open(child: &Child) -> io::Result<OwnedFd> {
+    let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, child.id() as libc::pid_t, 0) };
+    if fd == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: the syscall has just opened the descriptor, nothing else owns it
+    Ok(unsafe { OwnedFd::from_raw_fd(fd as RawFd) })
+}
+
+/// Notes the exit of the watched children until the process ends.
+fn watch_exits(mut wake_up: PipeReader, pending: Arc<Mutex<Vec<Watched>>>) {
+    let mut watched: Vec<Watched> = Vec::new();
+    loop {
+        let mut fds: Vec<libc::pollfd> = std::iter::once(wake_up.as_raw_fd())
+            .chain(watched.iter().map(|watched| watched.pidfd.as_raw_fd()))
+            .map(|fd| libc::pollfd { fd, events: libc::POLLIN, revents: 0 })
+            .collect();
+        if unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, -1) } == -1 {
+            match io::Error::last_os_error().kind() {
+                io::ErrorKind::Interrupted => continue,
+                _ => return,
+            }
+        }
+
+        let now = Instant::now();
+        let mut revents = fds[1..].iter().map(|fd| fd.revents);
+        watched.retain(|watched| {
+            let revents = revents.next().unwrap_or(0);
+            if revents & libc::POLLIN != 0 {
+                if let Ok(mut exited_at) = watched.exited_at.lock() {
+                    *exited_at = Some(now);
+                }
+            }
+            revents == 0
+        });
+
+        if fds[0].revents != 0 {
+            let mut buf = [0u8; 64];
+            if matches!(wake_up.read(&mut buf), Ok(0) | Err(_)) {
+                return;
+            }
+            if let Ok(mut pending) = pending.lock() {
+                watched.append(&mut pending);
+            }
+        }
+    }
+}
+
+/// A running process and all of its descendants.
+fn process_tree(child: &Child) -> Vec<u32> {
+    let mut tree = Vec::new();
+    let mut pids = vec![child.id()];
+
+    while let Some(pid) = pids.pop() {
+        tree.push(pid);
+        let children_path = format!("/proc/{pid}/task/{pid}/children");
+        let children = std::fs::read_to_string(children_path).unwrap_or_default();
+        pids.extend(children.split_whitespace().filter_map(|pid| pid.parse::<u32>().ok()));
+    }
+    tree
+}
+
+/// CPU time used so far by a running process and all of its descendants.
+pub fn cpu_time_so_far(child: &Child) -> Duration {
+    let ticks_per_second = unsafe { libc::sysconf(libc::_SC_CLK_TCK) }.max(1) as u64;
+    let ticks: u64 = process_tree(child).into_iter().map(|pid| cpu_ticks(pid).unwrap_or(0)).sum();
+
+    Duration::from_secs(ticks / ticks_per_second)
+        + Duration::from_nanos(ticks % ticks_per_second * 1_000_000_000 / ticks_per_second)
+}
+
+/// Resident memory used right now by a running process and all of its
+/// descendants, in bytes.
+pub fn memory_so_far(child: &Child) -> u64 {
+    process_tree(child).into_iter().map(|pid| resident_memory(pid).unwrap_or(0)).sum()
+}
+
+/// Resident set size of a single process, in bytes.
+fn resident_memory(pid: u32) -> Option<u64> {
+    let status = std::fs::read_to_string(format!("/proc/{pid}/status")).ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kilobytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+/// User and system time of a single process, in clock ticks.
+fn cpu_ticks(pid: u32) -> Option<u64> {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // The name of the executable may contain spaces, fields are counted after it
+    let fields: Vec<&str> = stat.get(stat.rfind(')')? + 2..)?.split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some(utime + stime)
+}
+
+/// Limits the size of files written by the spawned process to `bytes`.
+/// Writing past it kills the process with `SIGXFSZ`. One more byte is let
+/// through, so that going over the limit can be told apart from reaching it.
+pub fn limit_output(command: &mut Command, bytes: u64) {
+    set_rlimit(command, libc::RLIMIT_FSIZE, bytes.saturating_add(1));
+}
+
+/// Limits the heap and other private writable memory of the spawned process
+/// to `bytes`. Allocating more fails. Address space which is only reserved,
+/// and not writable yet, does not count.
+pub fn limit_memory(command: &mut Command, bytes: u64) {
+    set_rlimit(command, libc::RLIMIT_DATA, bytes);
+}
+
+fn set_rlimit(command: &mut Command, resource: libc::__rlimit_resource_t, bytes: u64) {
+    let limit = libc::rlimit {
+        rlim_cur: bytes as libc::rlim_t,
+        rlim_max: bytes as libc::rlim_t,
+    };
+
+    // SAFETY: `setrlimit` is async-signal-safe and nothing is allocated
+    // between fork and exec.
+    unsafe {
+        command.pre_exec(move || {
+            if libc::setrlimit(resource, &limit) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exits_are_noted_without_waiting() {
+        let children: Vec<Child> = (0..20).map(|_| Command::new("true").spawn().unwrap()).collect();
+        let watchers: Vec<ExitWatcher> = children.iter().map(ExitWatcher::watch).collect();
+        std::thread::sleep(Duration::from_millis(200));
+        let checked_at = Instant::now();
+        for (child, watcher) in children.iter().zip(&watchers) {
+            let exited_at = watcher.exited_at().expect("exit to be noted");
+            assert!(exited_at < checked_at);
+            assert!(try_wait(child).unwrap().is_some(), "child to be left for reaping");
+        }
+    }
+
+    #[test]
+    fn running_child_has_not_exited() {
+        let mut child = Command::new("sleep").arg("10").spawn().unwrap();
+        let watcher = ExitWatcher::watch(&child);
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(watcher.exited_at(), None);
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+}
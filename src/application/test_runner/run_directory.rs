@@ -0,0 +1,79 @@
+use std::os::unix::fs::DirBuilderExt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::to_string;
+
+/// Name of the compiled solution inside of the directory
+const BINARY_NAME: &str = "solution";
+
+/// Private directory of the build of the solution, or of the inputs and
+/// outputs of all test cases of a single run. It is removed when dropped,
+/// unless the user asked to keep the artifacts.
+#[derive(Debug)]
+pub struct RunDirectory {
+    path: PathBuf,
+    keep: bool,
+}
+
+impl RunDirectory {
+    /// Creates a private directory, whose name starts with `kind`. If
+    /// `artifacts_dir` is given, the directory is created inside of it and
+    /// kept after the run.
+    pub fn new(artifacts_dir: Option<&str>, kind: &str) -> Result<Self, String> {
+        let (parent, keep) = match artifacts_dir {
+            Some(dir) => (PathBuf::from(dir), true),
+            None => (std::env::temp_dir(), false),
+        };
+        std::fs::create_dir_all(&parent).map_err(to_string)?;
+        // Programs started in another working directory get paths inside
+        let parent = parent.canonicalize().map_err(to_string)?;
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(to_string)?;
+        let path = parent.join(format!(
+            "algonds-{}-{}-{}.{:09}",
+            kind,
+            std::process::id(),
+            timestamp.as_secs(),
+            timestamp.subsec_nanos()
+        ));
+
+        std::fs::DirBuilder::new()
+            .mode(0o700)
+            .create(&path)
+            .map_err(to_string)?;
+
+        Ok(Self { path, keep })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Fills in `{tmpdir}`, `{bin}` and `{src}` in a compile or run command.
+    pub fn expand(&self, template: &str, source: Option<&str>) -> Result<String, String> {
+        let tmpdir = self.path.to_string_lossy();
+        let bin = self.path.join(BINARY_NAME);
+        let mut command = template
+            .replace("{tmpdir}", &shlex::quote(&tmpdir))
+            .replace("{bin}", &shlex::quote(&bin.to_string_lossy()));
+
+        if command.contains("{src}") {
+            let source = source.ok_or("The commands use {src}, but the path to the solution is not set")?;
+            // Made absolute, as the commands may run in another directory
+            let source = std::path::absolute(source).map_err(to_string)?;
+            command = command.replace("{src}", &shlex::quote(&source.to_string_lossy()));
+        }
+        Ok(command)
+    }
+}
+
+impl Drop for RunDirectory {
+    fn drop(&mut self) {
+        if !self.keep {
+            let _ = std::fs::remove_dir_all(&self.path);
+        }
+    }
+}
@@ -1,13 +1,34 @@
 use std::{
-    process::Child,
-    time::Instant,
+    os::unix::process::ExitStatusExt,
+    path::{Path, PathBuf},
+    process::{Child, ExitStatus, Stdio},
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
-use super::{parse_command, TestCaseIO};
-use crate::application::common::TestCaseStatus;
+use super::checker::{self, CustomCheck, Verdict};
+use super::differential::{helper_config, start_helper};
+use super::resource_usage::{self, ExitWatcher, ResourceUsage};
+use super::sandbox;
+use super::test_case_io::truncate_middle;
+use super::{build_command, to_string, Interaction, TestCaseIO};
+use crate::application::common::{Checker, Clock, InputMode, Limits, RunConfig, TestCase, TestCaseStatus, Timing};
 
-fn remove_whitespace(s: &str) -> String {
-    s.chars().filter(|c| !c.is_whitespace()).collect()
+const INPUT_PLACEHOLDER: &str = "{input}";
+
+/// With CPU time limits, a solution which is stuck without using the CPU is
+/// killed once its wall time exceeds the limit this many times.
+const WALL_TIME_FACTOR: u32 = 3;
+
+/// Wall time the interactor may take if the problem has no time limit,
+/// otherwise it gets as much as a solution stuck without using the CPU.
+const INTERACTOR_TIME_LIMIT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy)]
+struct Exit {
+    status: ExitStatus,
+    usage: ResourceUsage,
+    wall_time: Duration,
 }
 
 #[derive(Debug)]
@@ -15,105 +36,431 @@ struct RunnableTestCaseInner {
     pub io: TestCaseIO,
     pub process: Child,
     pub start_time: Instant,
+    pub watcher: ExitWatcher,
+    pub exit: Option<Exit>,
+    /// Memory used when the running process was found over the memory limit
+    pub memory_exceeded: Option<u64>,
+    pub interaction: Option<Interaction>,
+    pub check: Option<CustomCheck>,
 }
 
 impl RunnableTestCaseInner {
+    fn try_wait(&mut self) -> Result<Option<Exit>, String> {
+        if self.exit.is_none() {
+            let finished = resource_usage::try_wait(&self.process).map_err(to_string)?;
+            self.exit = finished.map(|(status, usage)| self.to_exit(status, usage));
+        }
+        Ok(self.exit)
+    }
+
+    fn to_exit(&self, status: ExitStatus, usage: ResourceUsage) -> Exit {
+        let exited_at = self.watcher.exited_at().unwrap_or_else(Instant::now);
+        Exit {
+            status,
+            usage,
+            wall_time: exited_at.duration_since(self.start_time),
+        }
+    }
+
+    /// Time used so far, or in total if the process has finished.
+    fn timing(&self) -> Timing {
+        match &self.exit {
+            Some(exit) => Timing {
+                wall: exit.wall_time,
+                cpu: exit.usage.cpu_time,
+            },
+            None => Timing {
+                wall: self.start_time.elapsed(),
+                cpu: resource_usage::cpu_time_so_far(&self.process),
+            },
+        }
+    }
+
+    /// Kills the solution first, so that an interaction which is waiting on
+    /// it can be stopped too.
     fn kill(&mut self) -> bool {
-        self.process.kill().is_ok()
+        let killed = self.exit.is_none() && self.process.kill().is_ok();
+        if self.exit.is_none() {
+            if let Ok((status, usage)) = resource_usage::wait(&self.process) {
+                self.exit = Some(self.to_exit(status, usage));
+            }
+        }
+        if let Some(check) = &mut self.check {
+            check.kill();
+        }
+        if let Some(interaction) = &mut self.interaction {
+            interaction.kill();
+        }
+        killed
     }
 }
 
+/// Expected output which is yet to be given by a reference solution.
+#[derive(Debug)]
+enum Reference {
+    /// Reference which is run once the test case is started
+    Pending(Arc<RunConfig>),
+    /// Reference running on the input, the test case is started in the
+    /// directory once it has finished
+    Running(Box<RunnableTestCase>, PathBuf),
+}
+
 #[derive(Debug)]
 pub struct RunnableTestCase {
     pub id: usize,
     pub complexity: u32,
-    pub command_template: String,
-    pub arg: String,
+    pub is_stress_test: bool,
+    pub input: String,
     pub expected_stdout: String,
+    pub limits: Limits,
+    pub config: Arc<RunConfig>,
 
     error: Option<String>,
+    reference: Option<Reference>,
     inner: Option<RunnableTestCaseInner>,
 }
 
 impl RunnableTestCase {
-    pub fn new(id: usize, complexity: u32, command_template: String, arg: String, expected_stdout: String) -> Self {
+    pub fn new(test_case: TestCase, config: Arc<RunConfig>) -> Self {
+        let reference = test_case
+            .reference
+            .map(|command| Reference::Pending(helper_config(command, config.input_mode, &config)));
         Self {
-            id,
-            command_template,
-            arg,
-            expected_stdout,
-            complexity,
+            id: test_case.id,
+            input: test_case.input,
+            expected_stdout: test_case.output,
+            complexity: test_case.complexity,
+            is_stress_test: test_case.is_stress_test,
+            limits: Limits {
+                time: test_case.time_limit_ms.map(Duration::from_millis).or(config.limits.time),
+                ..config.limits
+            },
+            config,
+
+            error: None,
+            reference,
+            inner: None,
+        }
+    }
+
+    /// Fresh copy of the test case, which can be run once more.
+    pub fn again(&self) -> Self {
+        Self {
+            id: self.id,
+            complexity: self.complexity,
+            is_stress_test: self.is_stress_test,
+            input: self.input.clone(),
+            expected_stdout: self.expected_stdout.clone(),
+            limits: self.limits,
+            config: Arc::clone(&self.config),
 
             error: None,
+            reference: match &self.reference {
+                Some(Reference::Pending(config)) => Some(Reference::Pending(Arc::clone(config))),
+                _ => None,
+            },
             inner: None,
         }
     }
 
-    pub fn start(&mut self) {
-        let result = self.start_inner();
+    /// Starts the solution, or the reference solution first if the expected
+    /// output is yet to be computed.
+    pub fn start(&mut self, dir: &Path) {
+        if let Some(Reference::Pending(config)) = &self.reference {
+            let reference = start_helper(config, self.id, self.input.clone(), dir);
+            self.reference = Some(Reference::Running(Box::new(reference), dir.to_path_buf()));
+            return;
+        }
+        let result = self.start_inner(dir);
         self.error = result.err();
     }
 
+    /// Takes the expected output from the reference solution once it has
+    /// finished, and starts the solution.
+    fn continue_reference(&mut self) {
+        let finished = match &mut self.reference {
+            Some(Reference::Running(reference, _)) => reference.has_finished() || reference.is_over_limit(),
+            _ => false,
+        };
+        if !finished {
+            return;
+        }
+
+        if let Some(Reference::Running(reference, dir)) = self.reference.take() {
+            match reference.into_output() {
+                Ok(output) => {
+                    self.expected_stdout = output;
+                    self.start(&dir);
+                }
+                Err(err_msg) => self.error = Some(format!("Reference solution failed: {}", err_msg)),
+            }
+        }
+    }
+
+    pub fn fail_to_start(&mut self, err_msg: impl Into<String>) {
+        self.error = Some(err_msg.into());
+    }
+
     pub fn kill(self) {
+        if let Some(Reference::Running(reference, _)) = self.reference {
+            reference.kill();
+        }
         self.inner.map(|mut i| i.kill());
     }
 
-    fn start_inner(&mut self) -> Result<(), String> {
-        let io = TestCaseIO::new()?;
+    fn start_inner(&mut self, dir: &Path) -> Result<(), String> {
+        let io = TestCaseIO::new(dir, self.id, &self.input)?;
         let (stdout, stderr) = io.get_io()?;
+        let interactive = self.config.interactor.is_some();
+        let (stdin, stdout) = match self.config.input_mode {
+            _ if interactive => (Stdio::piped(), Stdio::piped()),
+            InputMode::Stdin => (io.get_stdin()?, stdout),
+            InputMode::Argv | InputMode::File => (Stdio::null(), stdout),
+        };
+
+        let mut command = build_command(self.command(&io.input_path())?, &self.config.run_options)?;
+        if let Some(output_limit) = self.limits.output {
+            resource_usage::limit_output(&mut command, output_limit);
+        }
+        if let Some(memory_limit) = self.limits.memory {
+            resource_usage::limit_memory(&mut command, memory_limit);
+        }
+        if self.config.sandbox {
+            sandbox::sandbox(&mut command, &dir.join(format!("{}.scratch", self.id)))?;
+        }
 
-        let process = parse_command(format!("{} {}", self.command_template, self.arg))?
+        let mut process = command
+            .stdin(stdin)
             .stdout(stdout)
             .stderr(stderr)
             .spawn()
-            .map_err(|err| format!("{}", err))?;
-
-        let process = process;
+            .map_err(|err| match self.config.sandbox {
+                true => format!("Failed to start the solution in the sandbox: {}", err),
+                false => format!("{}", err),
+            })?;
         let start_time = Instant::now();
+        let watcher = ExitWatcher::watch(&process);
+
+        let interaction = match &self.config.interactor {
+            Some(interactor) => {
+                let time_limit = self.limits.time.map_or(INTERACTOR_TIME_LIMIT, |limit| limit * WALL_TIME_FACTOR);
+                let options = &self.config.run_options;
+                match Interaction::start(interactor, options, &io, &self.expected_stdout, &mut process, time_limit) {
+                    Ok(interaction) => Some(interaction),
+                    Err(err_msg) => {
+                        let _ = process.kill();
+                        let _ = process.wait();
+                        return Err(err_msg);
+                    }
+                }
+            }
+            None => None,
+        };
 
         self.inner = Some(RunnableTestCaseInner {
             io,
             process,
             start_time,
+            watcher,
+            exit: None,
+            memory_exceeded: None,
+            interaction,
+            check: None,
         });
         Ok(())
     }
 
-    pub fn has_started(&self) -> bool {
-        self.inner.is_some()
+    /// Builds the run command according to the way input is delivered.
+    fn command(&self, input_path: &str) -> Result<String, String> {
+        let command_template = &self.config.run_script;
+        let command = match self.config.input_mode {
+            _ if self.config.interactor.is_some() => command_template.clone(),
+            InputMode::Stdin => command_template.clone(),
+            // The shell must not run anything from the input, so its words
+            // are quoted one by one, the same way they are split otherwise
+            InputMode::Argv if self.config.run_options.shell => {
+                let args = shlex::split(&self.input).ok_or("Failed to split the input into arguments")?;
+                let args: Vec<String> = args.iter().map(|arg| shlex::quote(arg).into_owned()).collect();
+                format!("{} {}", command_template, args.join(" "))
+            }
+            InputMode::Argv => format!("{} {}", command_template, self.input),
+            InputMode::File if command_template.contains(INPUT_PLACEHOLDER) => command_template
+                .replace(INPUT_PLACEHOLDER, &shlex::quote(input_path)),
+            InputMode::File => format!("{} {}", command_template, shlex::quote(input_path)),
+        };
+        Ok(command)
     }
 
     pub fn has_finished(&mut self) -> bool {
         if self.error.is_some() {
             return true;
         }
+        if self.reference.is_some() {
+            self.continue_reference();
+            return self.error.is_some();
+        }
 
         if let Some(inner) = &mut self.inner {
-            let res = match inner.process.try_wait() {
+            let res = match inner.try_wait() {
                 Ok(Some(_)) => true,
                 Ok(None) => false,
                 Err(_err) => true,
             };
+            let interactor_finished = inner
+                .interaction
+                .as_mut()
+                .is_none_or(Interaction::has_finished);
 
-            return res;
+            return res && interactor_finished && self.custom_check_finished();
         }
 
         false
     }
 
+    /// Starts the custom checker once the solution has exited successfully
+    /// within its limits, and tells whether the checker has finished too.
+    fn custom_check_finished(&mut self) -> bool {
+        let config = Arc::clone(&self.config);
+        let command = match &config.checker {
+            Checker::Custom { command } if config.interactor.is_none() => command,
+            _ => return true,
+        };
+        if self.is_over_limit() {
+            return true;
+        }
+
+        let inner = match &mut self.inner {
+            Some(inner) if inner.exit.is_some_and(|exit| exit.status.success()) => inner,
+            _ => return true,
+        };
+        let check = match &mut inner.check {
+            Some(check) => check,
+            None => match CustomCheck::start(command, &config.run_options, &inner.io, &self.expected_stdout) {
+                Ok(check) => inner.check.insert(check),
+                Err(err_msg) => {
+                    self.error = Some(err_msg);
+                    return true;
+                }
+            },
+        };
+        check.has_finished()
+    }
+
+    /// Whether the process went past any of its limits, and should be
+    /// stopped and judged.
+    pub fn is_over_limit(&mut self) -> bool {
+        self.is_over_time_limit()
+            || self.is_over_output_limit()
+            || self.is_over_memory_limit()
+            || self.is_checker_over_time_limit()
+            || self.is_interactor_over_time_limit()
+    }
+
+    /// Whether the process is still running past its time limit.
+    fn is_over_time_limit(&self) -> bool {
+        match (&self.inner, self.limits.time) {
+            (Some(inner), Some(limit)) => Self::exceeds(inner.timing(), limit, self.config.clock),
+            _ => false,
+        }
+    }
+
+    /// Whether the process has written more output than it is allowed to.
+    fn is_over_output_limit(&self) -> bool {
+        match (&self.inner, self.limits.output) {
+            (Some(inner), Some(limit)) => inner.io.output_size() > limit,
+            _ => false,
+        }
+    }
+
+    /// Whether the running process uses more memory than it is allowed to,
+    /// which is noted down for judging it. The peak usage of a finished
+    /// process is checked when it is judged.
+    fn is_over_memory_limit(&mut self) -> bool {
+        let (inner, limit) = match (&mut self.inner, self.limits.memory) {
+            (Some(inner), Some(limit)) => (inner, limit),
+            _ => return false,
+        };
+        if inner.memory_exceeded.is_none() && inner.exit.is_none() {
+            let memory = resource_usage::memory_so_far(&inner.process);
+            inner.memory_exceeded = Some(memory).filter(|memory| *memory > limit);
+        }
+        inner.memory_exceeded.is_some()
+    }
+
+    /// Whether the custom checker is still running past its time limit.
+    fn is_checker_over_time_limit(&self) -> bool {
+        self.inner
+            .as_ref()
+            .and_then(|inner| inner.check.as_ref())
+            .is_some_and(CustomCheck::is_over_time_limit)
+    }
+
+    /// Whether the interactor is still running past its time limit.
+    fn is_interactor_over_time_limit(&self) -> bool {
+        self.inner
+            .as_ref()
+            .and_then(|inner| inner.interaction.as_ref())
+            .is_some_and(Interaction::is_over_time_limit)
+    }
+
+    fn exceeds(timing: Timing, limit: Duration, clock: Clock) -> bool {
+        match clock {
+            Clock::Wall => timing.wall > limit,
+            Clock::Cpu => timing.cpu > limit || timing.wall > limit * WALL_TIME_FACTOR,
+        }
+    }
+
     fn error_result(err_msg: impl Into<String>) -> TestCaseStatus {
         let err_msg = err_msg.into();
         TestCaseStatus::Err { err_msg }
     }
 
-    pub fn get_results(self) -> TestCaseStatus {
+    /// Stdout of a finished helper program, like a generator of inputs. Fails
+    /// unless the program exited successfully within its limits.
+    pub fn into_output(self) -> Result<String, String> {
+        if let Some(err_msg) = self.error {
+            return Err(err_msg);
+        }
+        let mut inner = self.inner.ok_or("Process has not even started.")?;
+        let exit = inner.try_wait()?;
+        let time = inner.timing();
+        if let Some(limit) = self.limits.time.filter(|limit| Self::exceeds(time, *limit, self.config.clock)) {
+            inner.kill();
+            return Err(format!("Time limit of {} ms exceeded", limit.as_millis()));
+        }
+        if self.limits.output.is_some_and(|limit| inner.io.output_size() > limit) {
+            inner.kill();
+            return Err("Output limit exceeded".to_string());
+        }
+
+        let exit = exit.ok_or("Process has not finished but should have")?;
+        if !exit.status.success() {
+            let stderr = inner.io.get_stderr().unwrap_or_default();
+            return Err(format!("Exited with {}. {}", exit.status, stderr.trim_end()));
+        }
+        inner.io.get_full_stdout()
+    }
+
+    /// Status of the finished test case, together with the transcript of the
+    /// exchange with the interactor if the problem is interactive.
+    pub fn get_results(mut self) -> (TestCaseStatus, Option<String>) {
+        let mut interaction = self.inner.as_mut().and_then(|inner| inner.interaction.take());
+        let status = self.judge(interaction.as_mut());
+        let transcript = interaction.map(|interaction| interaction.transcript());
+        (status, transcript)
+    }
+
+    fn judge(self, mut interaction: Option<&mut Interaction>) -> TestCaseStatus {
         let Self {
             id: _,
-            command_template: _,
-            arg: _,
+            input: _,
             complexity,
+            is_stress_test: _,
             expected_stdout,
+            limits,
+            config,
             error,
+            reference: _,
             inner,
         } = self;
 
@@ -121,49 +468,239 @@ impl RunnableTestCase {
             return Self::error_result(err_msg +"(While handling the test case)");
         };
 
-        let RunnableTestCaseInner {
-            mut io,
-            mut process,
-            start_time,
-        } = match inner {
+        let mut inner = match inner {
             Some(inner) => inner,
             None => return Self::error_result("Process has not even started."),
         };
 
-        let time = start_time.elapsed();
-        let exit_status = match process.try_wait() {
-            Ok(status) => status,
+        let exit = match inner.try_wait() {
+            Ok(exit) => exit,
             Err(_) => return Self::error_result("Error checking exit status."),
         };
 
-        match exit_status {
-            None => Self::error_result("Process has not finished but shuld have"),
-            Some(status) => {
+        // Measured before killing, as the sandbox hides the CPU time of
+        // killed solutions
+        let time = inner.timing();
+        if let Some(limit) = limits.time.filter(|limit| Self::exceeds(time, *limit, config.clock)) {
+            inner.kill();
+            if let Some(interaction) = interaction {
+                interaction.kill();
+            }
+            return TestCaseStatus::TimeLimitExceeded { limit, elapsed: time, complexity };
+        }
+
+        if let Some(interaction) = interaction.as_deref_mut().filter(|interaction| interaction.is_over_time_limit()) {
+            inner.kill();
+            interaction.kill();
+            let limit = interaction.time_limit().as_millis();
+            return Self::error_result(format!("Interactor did not finish within {} ms", limit));
+        }
+
+        // Runtimes which ignore `SIGXFSZ` keep running after hitting the limit
+        if let Some(limit) = limits.output {
+            let signaled = exit.is_some_and(|exit| exit.status.signal() == Some(libc::SIGXFSZ));
+            if signaled || inner.io.output_size() > limit {
+                inner.kill();
+                if let Some(interaction) = interaction {
+                    interaction.kill();
+                }
+                return TestCaseStatus::OutputLimitExceeded { limit };
+            }
+        }
+
+        if let (Some(limit), Some(memory)) = (limits.memory, inner.memory_exceeded) {
+            inner.kill();
+            if let Some(interaction) = interaction {
+                interaction.kill();
+            }
+            let max_rss = inner.exit.map_or(memory, |exit| exit.usage.max_rss.max(memory));
+            return TestCaseStatus::MemoryLimitExceeded { limit, max_rss };
+        }
+
+        let interactor_result = interaction.map(Interaction::finish);
+        let RunnableTestCaseInner { mut io, check, .. } = inner;
+
+        match exit {
+            None => Self::error_result("Process has not finished but should have"),
+            Some(Exit { status, usage: ResourceUsage { max_rss, .. }, .. }) => {
+                if config.sandbox && status.signal() == Some(sandbox::VIOLATION_SIGNAL) {
+                    return TestCaseStatus::SandboxViolation {
+                        message: "Solution was killed for making a forbidden system call".to_string(),
+                    };
+                }
+
+                if let Some(limit) = limits.memory.filter(|limit| max_rss > *limit) {
+                    return TestCaseStatus::MemoryLimitExceeded { limit, max_rss };
+                }
+
+                // The interactor has the final say, a solution which was
+                // rejected often crashes on the closed input afterwards.
+                if let Some(interactor_result) = interactor_result {
+                    match interactor_result {
+                        Ok((exit_status, _)) if exit_status.success() => {}
+                        Ok((exit_status, message)) => return TestCaseStatus::Fail {
+                            expected: truncate_middle(expected_stdout),
+                            actual: String::new(),
+                            message: format!("Interactor exited with {}. {}", exit_status, message),
+                            time,
+                            complexity,
+                            max_rss,
+                        },
+                        Err(err_msg) => return Self::error_result(err_msg + "(while waiting for the interactor)"),
+                    }
+
+                    if status.success() {
+                        return TestCaseStatus::Pass { time, complexity, max_rss, stats: None };
+                    }
+                }
+
                 if status.success() {
-                    let stdout = match io.get_stdout() {
+                    let stdout = match io.get_full_stdout() {
                         Ok(stdout) => stdout,
                         Err(err_msg) => return TestCaseStatus::Err { err_msg: err_msg + "(while cheching stdout)"}
                     };
 
-                    if remove_whitespace(&stdout) == remove_whitespace(&expected_stdout) {
-                        return TestCaseStatus::Pass { time, complexity };
-                    }
+                    let verdict = match (checker::check(&config.checker, &expected_stdout, &stdout), check) {
+                        (Some(verdict), _) => Ok(verdict),
+                        (None, Some(check)) => check.verdict(),
+                        (None, None) => Err("Checker has not been started".to_string()),
+                    };
+                    let message = match verdict {
+                        Ok(Verdict::Accepted) => return TestCaseStatus::Pass { time, complexity, max_rss, stats: None },
+                        Ok(Verdict::WrongAnswer { message }) => message,
+                        Err(err_msg) => return TestCaseStatus::Err { err_msg: err_msg + "(while checking stdout)" },
+                    };
 
                     return TestCaseStatus::Fail {
-                            expected: expected_stdout,
-                            actual: stdout,
+                            expected: truncate_middle(expected_stdout),
+                            actual: io.get_stdout().unwrap_or_default(),
+                            message,
                             time,
-                            complexity
+                            complexity,
+                            max_rss,
                         }
                 }
 
-                let err_msg = match io.get_stderr() {
+                let stderr = match io.get_stderr() {
                     Ok(stderr) => stderr,
-                    Err(err_msg) => err_msg,
+                    Err(err_msg) => return Self::error_result(err_msg + "(while reading stderr)"),
                 };
 
-                TestCaseStatus::Err { err_msg }
+                TestCaseStatus::RuntimeError {
+                    signal: status.signal(),
+                    code: status.code(),
+                    core_dumped: status.core_dumped(),
+                    stderr,
+                }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::super::{testing, RunDirectory};
+    use crate::application::common::{Checker, CommandOptions, Limits, RunConfig, TestCase, TestCaseStatus};
+
+    /// Fails unless started in a directory with `marker` and with `GREETING`
+    /// set, like the solution would be.
+    const NEEDS_RUN_OPTIONS: &str = r#"sh -c 'test -f marker && test "$GREETING" = hi'"#;
+
+    fn run_with_options(config: RunConfig) -> TestCaseStatus {
+        let working_dir = RunDirectory::new(None, "test").unwrap();
+        std::fs::write(working_dir.path().join("marker"), "").unwrap();
+        let config = RunConfig {
+            run_options: CommandOptions {
+                env: [("GREETING".to_string(), "hi".to_string())].into(),
+                working_dir: Some(working_dir.path().to_string_lossy().into_owned()),
+                ..CommandOptions::default()
+            },
+            ..config
+        };
+        testing::run(TestCase::default(), config).0
+    }
+
+    #[test]
+    fn interactor_gets_the_run_options() {
+        let status = run_with_options(RunConfig {
+            run_script: "true".to_string(),
+            interactor: Some(NEEDS_RUN_OPTIONS.to_string()),
+            ..RunConfig::default()
+        });
+        assert!(matches!(status, TestCaseStatus::Pass { .. }), "{:?}", status);
+    }
+
+    #[test]
+    fn checker_gets_the_run_options() {
+        let status = run_with_options(RunConfig {
+            run_script: "true".to_string(),
+            checker: Checker::Custom { command: NEEDS_RUN_OPTIONS.to_string() },
+            ..RunConfig::default()
+        });
+        assert!(matches!(status, TestCaseStatus::Pass { .. }), "{:?}", status);
+    }
+
+    #[test]
+    fn hanging_interactor_is_stopped() {
+        let config = RunConfig {
+            run_script: "true".to_string(),
+            interactor: Some("sh -c 'exec sleep 10'".to_string()),
+            limits: Limits { time: Some(Duration::from_millis(100)), ..Limits::default() },
+            ..RunConfig::default()
+        };
+        let start = Instant::now();
+        let status = testing::run(TestCase::default(), config).0;
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(status, TestCaseStatus::Err { err_msg: "Interactor did not finish within 300 ms".to_string() });
+    }
+
+    fn run_with_memory_limit(run_script: &str) -> TestCaseStatus {
+        let config = RunConfig {
+            run_script: run_script.to_string(),
+            run_options: CommandOptions { shell: true, ..CommandOptions::default() },
+            limits: Limits { time: Some(Duration::from_secs(10)), memory: Some(64 << 20), output: None },
+            ..RunConfig::default()
+        };
+        testing::run(TestCase::default(), config).0
+    }
+
+    #[test]
+    fn allocating_past_the_memory_limit_fails() {
+        let status = run_with_memory_limit(r#"perl -e '$x = "a" x (200 << 20)'"#);
+        assert!(matches!(status, TestCaseStatus::RuntimeError { .. }), "{:?}", status);
+    }
+
+    #[test]
+    fn memory_of_child_processes_adds_up() {
+        let child = r#"perl -e '$x = "a" x (24 << 20); sleep 2'"#;
+        let status = run_with_memory_limit(&format!("{child} & {child} & wait"));
+        assert!(matches!(status, TestCaseStatus::MemoryLimitExceeded { .. }), "{:?}", status);
+    }
+
+    #[test]
+    fn slow_solution_under_a_memory_limit_is_too_slow() {
+        let config = RunConfig {
+            run_script: "sleep 10".to_string(),
+            limits: Limits { time: Some(Duration::from_millis(100)), memory: Some(64 << 20), output: None },
+            ..RunConfig::default()
+        };
+        let status = testing::run(TestCase::default(), config).0;
+        assert!(matches!(status, TestCaseStatus::TimeLimitExceeded { .. }), "{:?}", status);
+    }
+
+    #[test]
+    fn blank_interactor_is_an_error() {
+        let status = testing::run(
+            TestCase::default(),
+            RunConfig {
+                run_script: "true".to_string(),
+                interactor: Some(" ".to_string()),
+                ..RunConfig::default()
+            },
+        )
+        .0;
+        assert!(matches!(status, TestCaseStatus::Err { .. }), "{:?}", status);
+    }
+}
@@ -0,0 +1,324 @@
+use std::ffi::{CStr, CString};
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::process::CommandExt;
+use std::path::Path;
+use std::process::Command;
+
+use super::to_string;
+
+const MOUNT_ATTR_RDONLY: u64 = 0x1;
+
+#[cfg(target_arch = "x86_64")]
+const AUDIT_ARCH: u32 = 0xc000_003e;
+#[cfg(target_arch = "aarch64")]
+const AUDIT_ARCH: u32 = 0xc000_00b7;
+
+/// Syscalls numbered above this on x86_64 belong to the x32 ABI.
+const X32_SYSCALL_BIT: u32 = 0x4000_0000;
+
+/// Offsets of the fields of `struct seccomp_data`
+const SECCOMP_DATA_NR: u32 = 0;
+const SECCOMP_DATA_ARCH: u32 = 4;
+
+/// System calls which kill the solution on the spot. A solution never needs
+/// any of them, they are only useful for escaping the sandbox or for messing
+/// with the rest of the machine.
+const FORBIDDEN_SYSCALLS: &[libc::c_long] = &[
+    libc::SYS_ptrace,
+    libc::SYS_process_vm_readv,
+    libc::SYS_process_vm_writev,
+    libc::SYS_pidfd_getfd,
+    libc::SYS_mount,
+    libc::SYS_umount2,
+    libc::SYS_mount_setattr,
+    libc::SYS_open_tree,
+    libc::SYS_move_mount,
+    libc::SYS_fsopen,
+    libc::SYS_fsconfig,
+    libc::SYS_fsmount,
+    libc::SYS_fspick,
+    libc::SYS_pivot_root,
+    libc::SYS_chroot,
+    libc::SYS_unshare,
+    libc::SYS_setns,
+    libc::SYS_reboot,
+    libc::SYS_kexec_load,
+    libc::SYS_init_module,
+    libc::SYS_finit_module,
+    libc::SYS_delete_module,
+    libc::SYS_bpf,
+    libc::SYS_perf_event_open,
+    libc::SYS_userfaultfd,
+    libc::SYS_keyctl,
+    libc::SYS_add_key,
+    libc::SYS_request_key,
+    libc::SYS_open_by_handle_at,
+    libc::SYS_fanotify_init,
+    libc::SYS_swapon,
+    libc::SYS_swapoff,
+    libc::SYS_acct,
+    libc::SYS_settimeofday,
+    libc::SYS_clock_settime,
+    libc::SYS_sethostname,
+    libc::SYS_setdomainname,
+];
+
+/// Signal which the kernel sends to a process killed by the seccomp filter.
+pub const VIOLATION_SIGNAL: libc::c_int = libc::SIGSYS;
+
+#[repr(C)]
+struct MountAttr {
+    attr_set: u64,
+    attr_clr: u64,
+    propagation: u64,
+    userns_fd: u64,
+}
+
+/// Everything the sandbox needs, prepared before forking, because nothing
+/// can be allocated between fork and exec.
+struct Sandbox {
+    uid_map: Vec<u8>,
+    gid_map: Vec<u8>,
+    scratch_dir: CString,
+    filter: Vec<libc::sock_filter>,
+}
+
+/// Makes `command` run in its own user, mount, network and PID namespaces.
+/// The whole filesystem is read-only except for `scratch_dir`, which also
+/// becomes `TMPDIR`, and dangerous system calls kill the process.
+pub fn sandbox(command: &mut Command, scratch_dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(scratch_dir).map_err(to_string)?;
+
+    let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+    let mut sandbox = Sandbox {
+        uid_map: format!("{uid} {uid} 1").into_bytes(),
+        gid_map: format!("{gid} {gid} 1").into_bytes(),
+        scratch_dir: CString::new(scratch_dir.as_os_str().as_bytes()).map_err(to_string)?,
+        filter: seccomp_filter()?,
+    };
+
+    command.env("TMPDIR", scratch_dir);
+
+    // SAFETY: the closure only makes async-signal-safe system calls and
+    // does not allocate.
+    unsafe {
+        command.pre_exec(move || sandbox.enter());
+    }
+    Ok(())
+}
+
+impl Sandbox {
+    fn enter(&mut self) -> io::Result<()> {
+        let namespaces = libc::CLONE_NEWUSER | libc::CLONE_NEWNS | libc::CLONE_NEWNET | libc::CLONE_NEWPID;
+        check(unsafe { libc::unshare(namespaces) })?;
+
+        write_file(c"/proc/self/setgroups", b"deny")?;
+        write_file(c"/proc/self/uid_map", &self.uid_map)?;
+        write_file(c"/proc/self/gid_map", &self.gid_map)?;
+
+        self.isolate_filesystem()?;
+
+        // Only children of this process end up in the new PID namespace
+        let pid = check(unsafe { libc::fork() })?;
+        if pid != 0 {
+            supervise(pid);
+        }
+
+        unsafe {
+            check(libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL))?;
+            check(libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0))?;
+            let program = libc::sock_fprog {
+                len: self.filter.len() as libc::c_ushort,
+                filter: self.filter.as_mut_ptr(),
+            };
+            check(libc::prctl(
+                libc::PR_SET_SECCOMP,
+                libc::SECCOMP_MODE_FILTER,
+                &program as *const libc::sock_fprog,
+            ))?;
+        }
+        Ok(())
+    }
+
+    fn isolate_filesystem(&self) -> io::Result<()> {
+        let root = c"/".as_ptr();
+        unsafe {
+            // Changes must not propagate back to the rest of the system
+            check(libc::mount(
+                std::ptr::null(),
+                root,
+                std::ptr::null(),
+                libc::MS_REC | libc::MS_PRIVATE,
+                std::ptr::null(),
+            ))?;
+
+            set_read_only(root, true, libc::AT_RECURSIVE as libc::c_uint)?;
+
+            // Bind mounts inherit the flags of the original mount
+            check(libc::mount(
+                self.scratch_dir.as_ptr(),
+                self.scratch_dir.as_ptr(),
+                std::ptr::null(),
+                libc::MS_BIND,
+                std::ptr::null(),
+            ))?;
+            set_read_only(self.scratch_dir.as_ptr(), false, 0)?;
+        }
+        Ok(())
+    }
+}
+
+unsafe fn set_read_only(path: *const libc::c_char, read_only: bool, flags: libc::c_uint) -> io::Result<()> {
+    let (attr_set, attr_clr) = match read_only {
+        true => (MOUNT_ATTR_RDONLY, 0),
+        false => (0, MOUNT_ATTR_RDONLY),
+    };
+    let attr = MountAttr {
+        attr_set,
+        attr_clr,
+        propagation: 0,
+        userns_fd: 0,
+    };
+    check(libc::syscall(
+        libc::SYS_mount_setattr,
+        libc::AT_FDCWD,
+        path,
+        flags,
+        &attr as *const MountAttr,
+        std::mem::size_of::<MountAttr>(),
+    ) as libc::c_int)?;
+    Ok(())
+}
+
+/// Body of the process which stays outside of the new PID namespace. It waits
+/// for the solution and dies the same way, so that the runner sees the real
+/// exit status and can kill the whole sandbox by killing this process.
+fn supervise(pid: libc::pid_t) -> ! {
+    unsafe {
+        // Nothing but the solution may hold the pipes and the files of the
+        // test case, otherwise the runner would wait for this process
+        libc::syscall(libc::SYS_close_range, 0, libc::c_uint::MAX, 0);
+
+        let mut status = 0;
+        while libc::waitpid(pid, &mut status, 0) == -1 {
+            if io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+                libc::_exit(1);
+            }
+        }
+
+        if libc::WIFSIGNALED(status) {
+            let signal = libc::WTERMSIG(status);
+            let no_core = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+            libc::setrlimit(libc::RLIMIT_CORE, &no_core);
+            libc::signal(signal, libc::SIG_DFL);
+            let mut signals: libc::sigset_t = std::mem::zeroed();
+            libc::sigemptyset(&mut signals);
+            libc::sigaddset(&mut signals, signal);
+            libc::sigprocmask(libc::SIG_UNBLOCK, &signals, std::ptr::null_mut());
+            libc::kill(libc::getpid(), signal);
+            libc::_exit(128 + signal);
+        }
+        libc::_exit(libc::WEXITSTATUS(status));
+    }
+}
+
+fn write_file(path: &CStr, contents: &[u8]) -> io::Result<()> {
+    unsafe {
+        let fd = check(libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC))?;
+        let written = libc::write(fd, contents.as_ptr().cast(), contents.len());
+        libc::close(fd);
+        if written != contents.len() as isize {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+fn check(res: libc::c_int) -> io::Result<libc::c_int> {
+    if res == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(res)
+    }
+}
+
+fn statement(code: u32, k: u32) -> libc::sock_filter {
+    libc::sock_filter {
+        code: code as u16,
+        jt: 0,
+        jf: 0,
+        k,
+    }
+}
+
+fn jump(code: u32, k: u32, jt: u8, jf: u8) -> libc::sock_filter {
+    libc::sock_filter {
+        code: code as u16,
+        jt,
+        jf,
+        k,
+    }
+}
+
+/// BPF program which kills the process on any of `FORBIDDEN_SYSCALLS` and on
+/// syscalls made through a foreign ABI, and allows everything else.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+fn seccomp_filter() -> Result<Vec<libc::sock_filter>, String> {
+    let load = libc::BPF_LD | libc::BPF_W | libc::BPF_ABS;
+    let mut filter = vec![
+        statement(load, SECCOMP_DATA_ARCH),
+        jump(libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K, AUDIT_ARCH, 1, 0),
+        statement(libc::BPF_RET | libc::BPF_K, libc::SECCOMP_RET_KILL_PROCESS),
+        statement(load, SECCOMP_DATA_NR),
+    ];
+
+    // Every check jumps forward to the final "kill" instruction on a match
+    let checks = FORBIDDEN_SYSCALLS.len() + usize::from(cfg!(target_arch = "x86_64"));
+    let mut to_kill = checks as u8;
+    if cfg!(target_arch = "x86_64") {
+        filter.push(jump(libc::BPF_JMP | libc::BPF_JGE | libc::BPF_K, X32_SYSCALL_BIT, to_kill, 0));
+        to_kill -= 1;
+    }
+    for &syscall in FORBIDDEN_SYSCALLS {
+        filter.push(jump(libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K, syscall as u32, to_kill, 0));
+        to_kill -= 1;
+    }
+
+    filter.push(statement(libc::BPF_RET | libc::BPF_K, libc::SECCOMP_RET_ALLOW));
+    filter.push(statement(libc::BPF_RET | libc::BPF_K, libc::SECCOMP_RET_KILL_PROCESS));
+    Ok(filter)
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn seccomp_filter() -> Result<Vec<libc::sock_filter>, String> {
+    Err("The sandbox is only supported on x86_64 and aarch64".to_string())
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::super::testing;
+    use crate::application::common::{InputMode, RunConfig, TestCase, TestCaseStatus};
+
+    fn run_sandboxed(run_script: &str) -> TestCaseStatus {
+        let config = RunConfig {
+            run_script: run_script.to_string(),
+            input_mode: InputMode::Stdin,
+            sandbox: true,
+            ..RunConfig::default()
+        };
+        testing::run(TestCase::default(), config).0
+    }
+
+    #[test]
+    fn allowed_syscalls_pass() {
+        let status = run_sandboxed("true");
+        assert!(matches!(status, TestCaseStatus::Pass { .. }), "{:?}", status);
+    }
+
+    #[test]
+    fn forbidden_syscall_is_a_violation() {
+        let status = run_sandboxed("chroot /");
+        assert!(matches!(status, TestCaseStatus::SandboxViolation { .. }), "{:?}", status);
+    }
+}
@@ -0,0 +1,334 @@
+use std::mem::discriminant;
+use std::path::Path;
+use std::sync::Arc;
+
+use super::differential::{helper_config, start_helper};
+use super::RunnableTestCase;
+use crate::application::common::{RunConfig, ShrinkTest, TestCase, TestCaseStatus};
+
+/// Outcome of checking on the shrinking.
+#[derive(Debug)]
+pub enum ShrinkProgress {
+    Running,
+    /// Another smaller input was tried
+    Tried { size: usize, tries: usize },
+    /// No part of the input can be removed anymore. Holds the minimised test
+    /// case, unless nothing could be removed at all.
+    Done(Option<Box<(TestCase, TestCaseStatus)>>),
+}
+
+/// Parts of the input removed at a time, from the coarsest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Level {
+    Lines,
+    Tokens,
+    Chars,
+}
+
+#[derive(Debug)]
+enum Step {
+    BruteForce(String, RunnableTestCase),
+    Solution(TestCase, RunnableTestCase),
+}
+
+/// Minimises the input of a failing test case with delta debugging. Chunks
+/// of lines, then of tokens, then of characters are removed, halving the
+/// chunks whenever none of them can be removed. A smaller input is kept if
+/// the solution still fails on it the same way. This repeats until a whole
+/// pass removes nothing.
+#[derive(Debug)]
+pub struct Shrinker {
+    brute_force: Option<Arc<RunConfig>>,
+    solution: Arc<RunConfig>,
+    id: usize,
+    original_id: usize,
+    original_size: usize,
+    best: TestCase,
+    best_status: TestCaseStatus,
+    level: Level,
+    /// Number of parts removed at a time, 0 before the level has started
+    chunk: usize,
+    position: usize,
+    removed_at: usize,
+    /// Whether anything was removed in the current pass
+    progress: bool,
+    tries: usize,
+    step: Option<Step>,
+}
+
+impl Shrinker {
+    pub fn new(test: ShrinkTest, solution: Arc<RunConfig>) -> Self {
+        let brute_force = test
+            .brute_force
+            .map(|command| helper_config(command, solution.input_mode, &solution));
+        let best = TestCase {
+            is_stress_test: false,
+            ..test.test_case
+        };
+
+        Self {
+            brute_force,
+            solution,
+            id: test.id,
+            original_id: best.id,
+            original_size: best.input.len(),
+            best,
+            best_status: test.status,
+            level: Level::Lines,
+            chunk: 0,
+            position: 0,
+            removed_at: 0,
+            progress: false,
+            tries: 0,
+            step: None,
+        }
+    }
+
+    /// Starts the next candidate, or checks on the current one.
+    pub fn poll(&mut self, dir: &Path) -> ShrinkProgress {
+        let step = match &mut self.step {
+            Some(step) => step,
+            None => {
+                return match self.next_candidate() {
+                    Some(input) => {
+                        self.step = Some(self.start(input, dir));
+                        ShrinkProgress::Running
+                    }
+                    None => ShrinkProgress::Done(self.result()),
+                }
+            }
+        };
+
+        let process = match step {
+            Step::BruteForce(_, process) | Step::Solution(_, process) => process,
+        };
+        if !(process.has_finished() || process.is_over_limit()) {
+            return ShrinkProgress::Running;
+        }
+
+        match self.step.take() {
+            Some(Step::BruteForce(input, brute_force)) => match brute_force.into_output() {
+                Ok(output) => {
+                    self.step = Some(self.start_solution(input, output, dir));
+                    return ShrinkProgress::Running;
+                }
+                // The brute force rejects inputs which are not valid anymore
+                Err(_) => self.tries += 1,
+            },
+            Some(Step::Solution(test_case, solution)) => {
+                self.tries += 1;
+                self.accept(test_case, solution.get_results().0);
+            }
+            None => {}
+        }
+
+        ShrinkProgress::Tried {
+            size: self.best.input.len(),
+            tries: self.tries,
+        }
+    }
+
+    pub fn original_id(&self) -> usize {
+        self.original_id
+    }
+
+    pub fn original_size(&self) -> usize {
+        self.original_size
+    }
+
+    pub fn kill(self) {
+        match self.step {
+            Some(Step::BruteForce(_, process)) | Some(Step::Solution(_, process)) => process.kill(),
+            None => {}
+        }
+    }
+
+    fn result(&self) -> Option<Box<(TestCase, TestCaseStatus)>> {
+        if self.best.input.len() >= self.original_size {
+            return None;
+        }
+        let test_case = TestCase {
+            id: self.id,
+            complexity: self.best.input.len() as u32,
+            ..self.best.clone()
+        };
+        Some(Box::new((test_case, self.best_status.clone())))
+    }
+
+    /// Keeps the last candidate if the solution still fails on it the same way.
+    fn accept(&mut self, test_case: TestCase, status: TestCaseStatus) {
+        if same_failure(&status, &self.best_status) {
+            self.best = test_case;
+            self.best_status = status;
+            self.position = self.removed_at;
+            self.progress = true;
+        }
+    }
+
+    /// Input with the next chunk removed, `None` once the input is minimal.
+    fn next_candidate(&mut self) -> Option<String> {
+        loop {
+            let parts = split(&self.best.input, self.level);
+            if self.chunk == 0 {
+                self.chunk = (parts.len() / 2).max(1);
+                self.position = 0;
+            }
+
+            if self.position < parts.len() {
+                let end = (self.position + self.chunk).min(parts.len());
+                let candidate = parts[..self.position].concat() + &parts[end..].concat();
+                self.removed_at = self.position;
+                self.position = end;
+                return Some(candidate);
+            }
+
+            if self.chunk > 1 {
+                self.chunk /= 2;
+                self.position = 0;
+                continue;
+            }
+
+            self.chunk = 0;
+            self.level = match self.level {
+                Level::Lines => Level::Tokens,
+                Level::Tokens => Level::Chars,
+                Level::Chars if self.progress => Level::Lines,
+                Level::Chars => return None,
+            };
+            if self.level == Level::Lines {
+                self.progress = false;
+            }
+        }
+    }
+
+    fn start(&self, input: String, dir: &Path) -> Step {
+        match &self.brute_force {
+            Some(brute_force) => Step::BruteForce(input.clone(), start_helper(brute_force, self.id, input, dir)),
+            None => self.start_solution(input, self.best.output.clone(), dir),
+        }
+    }
+
+    fn start_solution(&self, input: String, output: String, dir: &Path) -> Step {
+        let test_case = TestCase {
+            input,
+            output,
+            ..self.best.clone()
+        };
+        let mut solution = RunnableTestCase::new(test_case.clone(), Arc::clone(&self.solution));
+        solution.start(dir);
+        Step::Solution(test_case, solution)
+    }
+}
+
+/// Whether the solution failed the same way, crashes must have the same
+/// signal or exit code.
+fn same_failure(status: &TestCaseStatus, original: &TestCaseStatus) -> bool {
+    match (status, original) {
+        (
+            TestCaseStatus::RuntimeError { signal, code, .. },
+            TestCaseStatus::RuntimeError { signal: original_signal, code: original_code, .. },
+        ) => signal == original_signal && code == original_code,
+        _ => discriminant(status) == discriminant(original),
+    }
+}
+
+/// Splits the input into parts which are removed, concatenating all of them
+/// gives back the input. Tokens keep the whitespace which follows them.
+fn split(input: &str, level: Level) -> Vec<&str> {
+    match level {
+        Level::Lines => input.split_inclusive('\n').collect(),
+        Level::Tokens => {
+            let mut starts = vec![0];
+            let mut after_whitespace = false;
+            for (i, c) in input.char_indices() {
+                if after_whitespace && !c.is_whitespace() && i > 0 {
+                    starts.push(i);
+                }
+                after_whitespace = c.is_whitespace();
+            }
+            starts.push(input.len());
+            starts.windows(2).map(|w| &input[w[0]..w[1]]).filter(|part| !part.is_empty()).collect()
+        }
+        Level::Chars => input
+            .char_indices()
+            .map(|(i, c)| &input[i..i + c.len_utf8()])
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUTS: &[&str] = &["", "\n", "1 2 3\n", "  ab cd\n e", "3\n1 2\n\n 4  5 \n", "zażółć\ngęślą jaźń"];
+
+    fn shrinker(input: &str) -> Shrinker {
+        let test_case = TestCase { input: input.to_string(), ..TestCase::default() };
+        let status = TestCaseStatus::Err { err_msg: String::new() };
+        Shrinker::new(ShrinkTest { test_case, status, brute_force: None, id: 1 }, Arc::default())
+    }
+
+    /// Shrinks without running anything, the solution fails the same way on
+    /// the candidates for which `fails` holds. Returns the result and the
+    /// number of candidates tried.
+    fn shrink(input: &str, fails: impl Fn(&str) -> bool) -> (String, usize) {
+        let mut shrinker = shrinker(input);
+        let mut tries = 0;
+        while let Some(candidate) = shrinker.next_candidate() {
+            tries += 1;
+            assert!(tries < 10_000, "shrinking {:?} does not terminate", input);
+            let status = match fails(&candidate) {
+                true => shrinker.best_status.clone(),
+                false => TestCaseStatus::Cancelled,
+            };
+            let test_case = TestCase { input: candidate, ..shrinker.best.clone() };
+            shrinker.accept(test_case, status);
+        }
+        (shrinker.best.input, tries)
+    }
+
+    #[test]
+    fn split_levels() {
+        assert_eq!(split("a\nb\n\nc", Level::Lines), ["a\n", "b\n", "\n", "c"]);
+        assert_eq!(split("  ab cd\n e", Level::Tokens), ["  ", "ab ", "cd\n ", "e"]);
+        assert_eq!(split("aé\n", Level::Chars), ["a", "é", "\n"]);
+    }
+
+    #[test]
+    fn split_gives_back_the_input() {
+        for input in INPUTS {
+            for level in [Level::Lines, Level::Tokens, Level::Chars] {
+                let parts = split(input, level);
+                assert_eq!(parts.concat(), *input, "{:?} at {:?}", input, level);
+                assert!(parts.iter().all(|part| !part.is_empty()), "{:?} at {:?}", input, level);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_input_is_minimal() {
+        assert_eq!(shrink("", |_| true), (String::new(), 0));
+    }
+
+    #[test]
+    fn terminates_when_nothing_can_be_removed() {
+        for input in INPUTS {
+            let (best, _) = shrink(input, |_| false);
+            assert_eq!(best, *input);
+        }
+    }
+
+    #[test]
+    fn terminates_when_everything_can_be_removed() {
+        for input in INPUTS {
+            assert_eq!(shrink(input, |_| true).0, "");
+        }
+    }
+
+    #[test]
+    fn finds_minimal_input() {
+        let fails = |input: &str| input.contains('7') && input.contains("42");
+        let (best, _) = shrink("5\n1 2 3\n7 8 9\n10 42 11\n", fails);
+        assert_eq!(best, "742");
+    }
+}
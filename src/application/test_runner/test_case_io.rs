@@ -1,28 +1,131 @@
-use std::{fs::File, io::Read, process::Stdio};
+use std::{fs::File, io::{Read, Seek, SeekFrom}, path::{Path, PathBuf}, process::Stdio};
 
 use super::to_string;
 
-const STDOUT_LOC: &str = "/tmp/algonds_tc_stdout.txt";
-const STDERR_LOC: &str = "/tmp/algonds_tc_stderr.txt";
+/// Bytes kept from each end of an output shown in the UI.
+const KEPT_BYTES: usize = 4 * 1024;
+
+/// Keeps only the beginning and the end of a long text, so that huge outputs
+/// are not held in memory by the UI.
+pub fn truncate_middle(text: String) -> String {
+    if text.len() <= 2 * KEPT_BYTES {
+        return text;
+    }
+    let head = floor_char_boundary(&text, KEPT_BYTES);
+    let tail = ceil_char_boundary(&text, text.len() - KEPT_BYTES);
+    format!(
+        "{}\n... ({} bytes omitted) ...\n{}",
+        &text[..head],
+        tail - head,
+        &text[tail..]
+    )
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+fn ceil_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+/// Reads the beginning and the end of a file, without loading the middle.
+fn read_bounded(path: &Path) -> Result<String, String> {
+    let mut file = File::open(path).map_err(to_string)?;
+    let len = file.metadata().map_err(to_string)?.len() as usize;
+    if len <= 2 * KEPT_BYTES {
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf).map_err(to_string)?;
+        return Ok(String::from_utf8_lossy(&buf).into_owned());
+    }
+
+    let mut head = vec![0; KEPT_BYTES];
+    file.read_exact(&mut head).map_err(to_string)?;
+    let mut tail = vec![0; KEPT_BYTES];
+    file.seek(SeekFrom::End(-(KEPT_BYTES as i64))).map_err(to_string)?;
+    file.read_exact(&mut tail).map_err(to_string)?;
+    Ok(format!(
+        "{}\n... ({} bytes omitted) ...\n{}",
+        String::from_utf8_lossy(&head),
+        len - 2 * KEPT_BYTES,
+        String::from_utf8_lossy(&tail)
+    ))
+}
 
 /// This struct keeps track of IO of a test case
 #[derive(Debug)]
 pub struct TestCaseIO {
+    stdin_loc: PathBuf,
+    stdout_loc: PathBuf,
+    stderr_loc: PathBuf,
+    answer_loc: PathBuf,
+    interactor_log_loc: PathBuf,
+    checker_log_loc: PathBuf,
     stderr_file: File,
     stdout_file: File,
 }
 
 impl TestCaseIO {
-    pub fn new() -> Result<Self, String> {
-        let stdout_file = File::create(STDOUT_LOC).map_err(to_string)?;
-        let stderr_file = File::create(STDERR_LOC).map_err(to_string)?;
+    /// Writes the test case input to a file, so that it can be read by the
+    /// solution without the runner ever blocking on a pipe. Every test case
+    /// gets its own files inside of the directory of the run.
+    pub fn new(dir: &Path, id: usize, input: &str) -> Result<Self, String> {
+        let stdin_loc = dir.join(format!("{id}.in"));
+        let stdout_loc = dir.join(format!("{id}.out"));
+        let stderr_loc = dir.join(format!("{id}.err"));
+        let answer_loc = dir.join(format!("{id}.ans"));
+        let interactor_log_loc = dir.join(format!("{id}.interactor.err"));
+        let checker_log_loc = dir.join(format!("{id}.checker.out"));
+
+        std::fs::write(&stdin_loc, input).map_err(to_string)?;
+        let stdout_file = File::create(&stdout_loc).map_err(to_string)?;
+        let stderr_file = File::create(&stderr_loc).map_err(to_string)?;
 
         Ok(Self {
+            stdin_loc,
+            stdout_loc,
+            stderr_loc,
+            answer_loc,
+            interactor_log_loc,
+            checker_log_loc,
             stderr_file,
             stdout_file,
         })
     }
 
+    pub fn input_path(&self) -> String {
+        self.stdin_loc.to_string_lossy().into_owned()
+    }
+
+    pub fn output_path(&self) -> String {
+        self.stdout_loc.to_string_lossy().into_owned()
+    }
+
+    /// Writes the expected output next to the actual one and returns its path.
+    pub fn write_answer(&self, expected: &str) -> Result<String, String> {
+        std::fs::write(&self.answer_loc, expected).map_err(to_string)?;
+        Ok(self.answer_loc.to_string_lossy().into_owned())
+    }
+
+    pub fn interactor_log_path(&self) -> String {
+        self.interactor_log_loc.to_string_lossy().into_owned()
+    }
+
+    pub fn checker_log_path(&self) -> String {
+        self.checker_log_loc.to_string_lossy().into_owned()
+    }
+
+    pub fn get_stdin(&self) -> Result<Stdio, String> {
+        let stdin: Stdio = File::open(&self.stdin_loc).map_err(to_string)?.into();
+        Ok(stdin)
+    }
+
     pub fn get_io(&self) -> Result<(Stdio, Stdio), String> {
         let stdout: Stdio = self.stdout_file.try_clone().map_err(to_string)?.into();
         let stderr: Stdio = self.stderr_file.try_clone().map_err(to_string)?.into();
@@ -30,21 +133,34 @@ impl TestCaseIO {
         Ok((stdout, stderr))
     }
 
+    /// Stdout of the solution, with the middle left out if it is long.
     pub fn get_stdout(&mut self) -> Result<String, String> {
+        read_bounded(&self.stdout_loc)
+    }
+
+    /// Whole stdout, for checking it or for passing it on. It is never more
+    /// than the output limit.
+    pub fn get_full_stdout(&mut self) -> Result<String, String> {
         let mut buf = String::new();
-        File::open(STDOUT_LOC)
-            .unwrap()
+        File::open(&self.stdout_loc)
+            .map_err(to_string)?
             .read_to_string(&mut buf)
             .map_err(to_string)?;
         Ok(buf)
     }
 
+    /// Stderr of the solution, with the middle left out if it is long.
     pub fn get_stderr(&mut self) -> Result<String, String> {
-        let mut buf = String::new();
-        File::open(STDERR_LOC)
-            .unwrap()
-            .read_to_string(&mut buf)
-            .map_err(to_string)?;
-        Ok(buf)
+        read_bounded(&self.stderr_loc)
+    }
+
+    /// Size of the larger of stdout and stderr written so far, in bytes.
+    pub fn output_size(&self) -> u64 {
+        [&self.stdout_file, &self.stderr_file]
+            .iter()
+            .filter_map(|file| file.metadata().ok())
+            .map(|metadata| metadata.len())
+            .max()
+            .unwrap_or(0)
     }
 }
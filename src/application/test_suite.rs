@@ -1,17 +1,40 @@
-use crate::data::generate_stress_tests_for;
+use crate::application::stress_test;
 use std::rc::Rc;
 use crate::application::Problem;
 use crate::application::TestCase;
+use crate::application::RunDetails;
 use crate::application::RunResponse;
 use std::cell::RefCell;
 use crate::application::test_runner::CodeRunner;
 use crate::application::TestCaseStatus;
+use crate::application::CompilationStatus;
+use crate::application::InputMode;
+use crate::application::Limits;
+use crate::application::Checker;
+use crate::application::Benchmark;
+use crate::application::Settings;
+use crate::application::RunConfig;
+use crate::application::RunSubset;
+use crate::application::DifferentialStatus;
+use crate::application::DifferentialTest;
+use crate::application::ShrinkStatus;
+use crate::application::ShrinkTest;
 use std::cell::Cell;
 
 pub struct TestSuite {
     new_test_cases_arrived: Cell<bool>,
+    problem: RefCell<Option<Rc<Problem>>>,
     test_cases: RefCell<Vec<TestCase>>,
     test_case_statuses: RefCell<Vec<TestCaseStatus>>,
+    compilation_status: RefCell<CompilationStatus>,
+    transcripts: RefCell<Vec<Option<String>>>,
+    /// Why the stress tests of the problem could not be generated
+    stress_test_error: RefCell<Option<String>>,
+    differential_status: RefCell<Option<DifferentialStatus>>,
+    shrink_status: RefCell<Option<ShrinkStatus>>,
+    /// Test cases from this id on were added while solving, like inputs
+    /// found by differential testing
+    first_ad_hoc_id: Cell<usize>,
     code_runner: CodeRunner, 
 }
 
@@ -19,8 +42,15 @@ impl TestSuite {
     pub fn new() -> Self {
         Self {
             new_test_cases_arrived: Cell::default(),
+            problem: RefCell::default(),
             test_cases: RefCell::default(),
             test_case_statuses: RefCell::default(),
+            compilation_status: RefCell::default(),
+            transcripts: RefCell::default(),
+            stress_test_error: RefCell::default(),
+            differential_status: RefCell::default(),
+            shrink_status: RefCell::default(),
+            first_ad_hoc_id: Cell::default(),
             code_runner: CodeRunner::default(),
         }
     }
@@ -39,11 +69,41 @@ impl TestSuite {
         self.new_test_cases_arrived.set(true);
 
         let mut test_cases = self.test_case_statuses.borrow_mut();
-        for RunResponse { id, status } in updates.into_iter() {
-            let to_edit = test_cases
-                .get_mut(id)
-                .unwrap_or_else(|| panic!("Could not apply update run details for example {id}"));
-            *to_edit = status;
+        for update in updates.into_iter() {
+            match update {
+                // Runs of the previous problem may still report test cases
+                // which the current one does not have
+                RunResponse::TestCase { id, status } => match test_cases.get_mut(id) {
+                    Some(to_edit) => *to_edit = status,
+                    None => eprintln!("Skipped the status of test case {id}, which does not exist"),
+                },
+                RunResponse::Compilation(status) => {
+                    *self.compilation_status.borrow_mut() = status;
+                }
+                RunResponse::Transcript { id, transcript } => {
+                    if let Some(to_edit) = self.transcripts.borrow_mut().get_mut(id) {
+                        *to_edit = Some(transcript);
+                    }
+                }
+                RunResponse::Differential(status) => {
+                    *self.differential_status.borrow_mut() = Some(status);
+                }
+                RunResponse::Shrink(status) => {
+                    *self.shrink_status.borrow_mut() = Some(status);
+                }
+                RunResponse::NewTestCase { test_case, status } => {
+                    let size = test_case.input.len();
+                    let id = self.add_test_case(test_case, status, &mut test_cases);
+                    let mut differential_status = self.differential_status.borrow_mut();
+                    if let Some(DifferentialStatus::Running { passed }) = *differential_status {
+                        *differential_status = Some(DifferentialStatus::Found { passed, id });
+                    }
+                    let mut shrink_status = self.shrink_status.borrow_mut();
+                    if let Some(ShrinkStatus::Running { original, .. }) = *shrink_status {
+                        *shrink_status = Some(ShrinkStatus::Done { original, size, id: Some(id) });
+                    }
+                }
+            }
         }
 
         true
@@ -54,6 +114,19 @@ impl TestSuite {
         self.test_case_statuses.borrow().clone()
     }
 
+    pub fn get_compilation_status(&self) -> CompilationStatus {
+        self.update_test_cases();
+        self.compilation_status.borrow().clone()
+    }
+
+    pub fn get_stress_test_error(&self) -> Option<String> {
+        self.stress_test_error.borrow().clone()
+    }
+
+    pub fn get_nth_transcript(&self, n: usize) -> Option<String> {
+        self.transcripts.borrow().get(n).cloned().flatten()
+    }
+
     pub fn get_nth_test_case(&self, n: usize) -> TestCaseStatus {
         self.test_case_statuses.borrow().get(n).unwrap().clone()
     }
@@ -65,30 +138,250 @@ impl TestSuite {
         changes
     }
 
-    pub fn run(&self, compile_script: String, run_script: String) {
-        self.reset_test_cases();
+    /// Runs the given subset of test cases. Does nothing if it is empty.
+    pub fn run(&self, settings: &Settings, subset: RunSubset) {
+        let test_cases = self.select(subset);
+        if test_cases.is_empty() {
+            return;
+        }
+
+        self.reset_test_cases(&test_cases);
+        self.code_runner
+            .please_run(self.run_details(settings, test_cases))
+            .unwrap();
+    }
+
+    fn run_details(&self, settings: &Settings, test_cases: Vec<TestCase>) -> RunDetails {
+        RunDetails {
+            compile_script: settings.compilation_step.clone(),
+            compile_options: settings.compile_options(),
+            config: self.run_config(settings),
+            source: settings.solution.clone(),
+            compile_inputs: settings.compile_inputs.clone(),
+            jobs: settings.jobs,
+            benchmark: Benchmark {
+                warmup: settings.warmup_runs,
+                runs: settings.benchmark_runs,
+            },
+            artifacts_dir: settings.artifacts_dir.clone(),
+            test_cases,
+            differential: None,
+            shrink: None,
+        }
+    }
+
+    /// Compares the solution with a brute force one on generated inputs,
+    /// until they differ.
+    pub fn run_differential(&self, settings: &Settings) {
+        let (generator, brute_force) = match (&settings.generator, &settings.brute_force) {
+            (Some(generator), Some(brute_force)) => (generator.clone(), brute_force.clone()),
+            _ => {
+                let err_msg = "Set both the generator and the brute force command first".to_string();
+                *self.differential_status.borrow_mut() = Some(DifferentialStatus::Failed { passed: 0, err_msg });
+                return;
+            }
+        };
+
+        *self.compilation_status.borrow_mut() = CompilationStatus::default();
+        *self.differential_status.borrow_mut() = Some(DifferentialStatus::Running { passed: 0 });
+        let differential = DifferentialTest {
+            generator,
+            brute_force,
+            id: self.number_of_tests(),
+        };
+        self.code_runner
+            .please_run(RunDetails {
+                differential: Some(differential),
+                ..self.run_details(settings, Vec::new())
+            })
+            .unwrap();
+    }
+
+    /// Minimises the input of a failing test case, keeping the original.
+    pub fn shrink(&self, settings: &Settings, id: usize) {
+        let test_case = self.test_cases.borrow().iter().find(|tc| tc.id == id).cloned();
+        let (test_case, status) = match (test_case, self.test_case_statuses.borrow().get(id)) {
+            (Some(test_case), Some(status)) => (test_case, status.clone()),
+            _ => return,
+        };
+
+        let err_msg = match &status {
+            TestCaseStatus::Fail { .. } if settings.brute_force.is_none() => {
+                Some("Shrinking a wrong answer needs the brute force command for the expected outputs")
+            }
+            TestCaseStatus::Fail { .. }
+            | TestCaseStatus::TimeLimitExceeded { .. }
+            | TestCaseStatus::MemoryLimitExceeded { .. }
+            | TestCaseStatus::OutputLimitExceeded { .. }
+            | TestCaseStatus::SandboxViolation { .. }
+            | TestCaseStatus::RuntimeError { .. } => None,
+            _ => Some("Only test cases which failed can be shrunk"),
+        };
+        if let Some(err_msg) = err_msg {
+            *self.shrink_status.borrow_mut() = Some(ShrinkStatus::Failed { err_msg: err_msg.to_string() });
+            return;
+        }
+
+        *self.compilation_status.borrow_mut() = CompilationStatus::default();
+        let original = test_case.input.len();
+        *self.shrink_status.borrow_mut() = Some(ShrinkStatus::Running { original: id, size: original, tries: 0 });
+        let shrink = ShrinkTest {
+            test_case,
+            status,
+            brute_force: settings.brute_force.clone(),
+            id: self.number_of_tests(),
+        };
         self.code_runner
-            .please_run(self.test_cases.borrow().clone(), compile_script, run_script)
+            .please_run(RunDetails {
+                shrink: Some(shrink),
+                ..self.run_details(settings, Vec::new())
+            })
             .unwrap();
     }
 
+    pub fn get_shrink_status(&self) -> Option<ShrinkStatus> {
+        self.shrink_status.borrow().clone()
+    }
+
+    /// Appends a test case, which is shown with the given status. Returns
+    /// its id.
+    fn add_test_case(&self, mut test_case: TestCase, status: TestCaseStatus, statuses: &mut Vec<TestCaseStatus>) -> usize {
+        let mut test_cases = self.test_cases.borrow_mut();
+        let id = test_cases.len();
+        test_case.id = id;
+        test_cases.push(test_case);
+        statuses.push(status);
+        self.transcripts.borrow_mut().push(None);
+        id
+    }
+
+    /// Whether the test case was added while solving, rather than coming
+    /// from the problem.
+    pub fn is_ad_hoc(&self, id: usize) -> bool {
+        id >= self.first_ad_hoc_id.get()
+    }
+
+    pub fn get_input(&self, id: usize) -> Option<String> {
+        self.test_cases.borrow().iter().find(|tc| tc.id == id).map(|tc| tc.input.clone())
+    }
+
+    pub fn get_differential_status(&self) -> Option<DifferentialStatus> {
+        self.differential_status.borrow().clone()
+    }
+
     pub fn stop(&self) {
         self.code_runner.please_stop().unwrap();
     }
 
+    pub fn shutdown(&self) {
+        let _ = self.code_runner.please_exit();
+    }
+
     pub fn set_test_cases_from(&self, problem: Rc<Problem>) {
         let mut test_cases = problem.test_cases.clone();
-        let last_id = test_cases.iter().map(|tc| tc.id).max().unwrap_or(0);
-        let mut stress_tests = generate_stress_tests_for(&problem.name, last_id);
-        test_cases.append(&mut stress_tests);
-    
+        let first_id = test_cases.iter().map(|tc| tc.id + 1).max().unwrap_or(0);
+        match stress_test::generate(&problem.stress_tests, first_id) {
+            Ok(mut stress_tests) => {
+                test_cases.append(&mut stress_tests);
+                *self.stress_test_error.borrow_mut() = None;
+            }
+            Err(err_msg) => *self.stress_test_error.borrow_mut() = Some(err_msg),
+        }
+
         let n = test_cases.len();
+        self.first_ad_hoc_id.set(n);
+        *self.differential_status.borrow_mut() = None;
+        *self.shrink_status.borrow_mut() = None;
+        *self.problem.borrow_mut() = Some(problem);
         *self.test_cases.borrow_mut() = test_cases;
         *self.test_case_statuses.borrow_mut() = vec![TestCaseStatus::default(); n];
+        *self.transcripts.borrow_mut() = vec![None; n];
+    }
+
+    fn run_config(&self, settings: &Settings) -> RunConfig {
+        let run_script = settings.run_step.clone();
+        match &*self.problem.borrow() {
+            Some(problem) => RunConfig {
+                run_options: settings.run_options(),
+                clock: settings.clock,
+                ..problem.run_config(run_script, settings.sandbox)
+            },
+            None => RunConfig {
+                run_script,
+                run_options: settings.run_options(),
+                input_mode: InputMode::default(),
+                limits: Limits::default(),
+                checker: Checker::default(),
+                interactor: None,
+                sandbox: settings.sandbox,
+                clock: settings.clock,
+            },
+        }
+    }
+
+    fn select(&self, subset: RunSubset) -> Vec<TestCase> {
+        let statuses = self.test_case_statuses.borrow();
+        self.test_cases
+            .borrow()
+            .iter()
+            .filter(|tc| match subset {
+                RunSubset::All => true,
+                RunSubset::Failed => statuses.get(tc.id).is_some_and(TestCaseStatus::is_failure),
+                RunSubset::Selected(id) => tc.id == id,
+                RunSubset::Samples => !tc.is_stress_test,
+            })
+            .cloned()
+            .collect()
+    }
+
+    fn reset_test_cases(&self, test_cases: &[TestCase]) {
+        *self.compilation_status.borrow_mut() = CompilationStatus::default();
+        let mut statuses = self.test_case_statuses.borrow_mut();
+        let mut transcripts = self.transcripts.borrow_mut();
+        for tc in test_cases {
+            if let Some(status) = statuses.get_mut(tc.id) {
+                *status = TestCaseStatus::default();
+            }
+            if let Some(transcript) = transcripts.get_mut(tc.id) {
+                *transcript = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test cases with their ids out of order, like in the problem database.
+    fn suite() -> TestSuite {
+        let suite = TestSuite::new();
+        *suite.test_cases.borrow_mut() = vec![
+            TestCase { id: 1, input: "second\n".to_string(), ..TestCase::default() },
+            TestCase { id: 0, input: "first\n".to_string(), ..TestCase::default() },
+        ];
+        *suite.test_case_statuses.borrow_mut() = vec![TestCaseStatus::default(); 2];
+        *suite.transcripts.borrow_mut() = vec![None; 2];
+        suite
+    }
+
+    #[test]
+    fn input_is_found_by_id() {
+        let suite = suite();
+        assert_eq!(suite.get_input(0).as_deref(), Some("first\n"));
+        assert_eq!(suite.get_input(1).as_deref(), Some("second\n"));
+        assert_eq!(suite.get_input(2), None);
+        suite.shutdown();
     }
 
-    fn reset_test_cases(&self) {
-        let n = self.number_of_tests();
-        *self.test_case_statuses.borrow_mut() = vec![TestCaseStatus::default(); n]
+    #[test]
+    fn shrink_finds_the_test_case_by_id() {
+        let suite = suite();
+        suite.test_case_statuses.borrow_mut()[0] =
+            TestCaseStatus::RuntimeError { signal: None, code: Some(1), core_dumped: false, stderr: String::new() };
+        suite.shrink(&Settings::default(), 0);
+        let status = suite.get_shrink_status();
+        suite.shutdown();
+        assert_eq!(status, Some(ShrinkStatus::Running { original: 0, size: "first\n".len(), tries: 0 }));
     }
 }
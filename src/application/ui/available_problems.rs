@@ -4,7 +4,8 @@ use crate::application::ui::UIElement;
 use crate::application::View;
 use std::cell::RefCell;
 use std::rc::Rc;
-use tui::widgets::Paragraph;
+use tui::layout::{Constraint, Direction, Layout};
+use tui::widgets::{Paragraph, Wrap};
 use tui::{
     backend::Backend,
     style::{Color, Modifier, Style},
@@ -17,6 +18,15 @@ pub struct AvailableProblems<'a> {
     items: Vec<ListItem<'a>>,
     cursor: String,
     list_state: RefCell<ListState>,
+    source_errors: Vec<String>,
+}
+
+/// Name of the problem, followed by the source it comes from.
+fn item<'a>(problem: &Problem) -> ListItem<'a> {
+    ListItem::new(Spans::from(vec![
+        Span::from(problem.name.clone()),
+        Span::styled(format!("  [{}]", problem.source), Style::default().fg(Color::DarkGray)),
+    ]))
 }
 
 impl<'a> UIElement for AvailableProblems<'a> {
@@ -26,14 +36,13 @@ impl<'a> UIElement for AvailableProblems<'a> {
         let list_state = view.get_list_state();
         let cursor = view.get_cursor();
         let problems: Vec<Rc<Problem>> = view.get_problems_to_select();
-        let items = problems
-            .into_iter()
-            .map(|prob| ListItem::new(Spans::from(Span::from(prob.name.clone()))))
-            .collect();
+        let items = problems.iter().map(|prob| item(prob)).collect();
+        let source_errors = view.source_errors();
         Self {
             items,
             cursor,
             list_state,
+            source_errors,
         }
     }
 
@@ -50,7 +59,26 @@ impl<'a> UIElement for AvailableProblems<'a> {
             )
             .highlight_symbol(&self.cursor);
 
-        frame.render_stateful_widget(list, layout.problem_list, &mut self.list_state.into_inner());
+        let area = match self.source_errors.is_empty() {
+            true => layout.problem_list,
+            false => {
+                let text: Vec<Spans> = self
+                    .source_errors
+                    .iter()
+                    .flat_map(|err_msg| err_msg.lines())
+                    .map(|line| Spans::from(Span::styled(line.to_string(), Style::default().fg(Color::Red))))
+                    .collect();
+                let height = (text.len() as u16).min(layout.problem_list.height / 2);
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(1), Constraint::Length(height)])
+                    .split(layout.problem_list);
+                frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }), chunks[1]);
+                chunks[0]
+            }
+        };
+
+        frame.render_stateful_widget(list, area, &mut self.list_state.into_inner());
         frame.render_widget(left_border, layout.problem_list_outline);
         frame.render_widget(right_border, layout.problem_preview_outline);
         frame.render_widget(
@@ -2,7 +2,7 @@ use crate::application::common::*;
 use crate::application::ui::ProblemMenuLayout;
 use crate::application::ui::UIElement;
 use crate::application::View;
-use tui::widgets::Paragraph;
+use tui::widgets::{Paragraph, Wrap};
 use tui::{
     backend::Backend,
     style::{Modifier, Style},
@@ -13,7 +13,18 @@ use tui::{
 pub struct CommandsView {
     compile_command: String,
     run_command: String,
+    solution: String,
+    language: Option<String>,
+    watch: String,
+    env: String,
+    working_dir: String,
+    compile_in_shell: bool,
+    run_in_shell: bool,
+    generator: String,
+    brute_force: String,
+    differential: Option<DifferentialStatus>,
     selected: Option<InputField>,
+    compilation: CompilationStatus,
 }
 
 impl UIElement for CommandsView {
@@ -22,24 +33,58 @@ impl UIElement for CommandsView {
     fn setup(view: &View) -> Self {
         let compile_command = view.compile_command_view();
         let run_command = view.run_command_view();
+        let solution = view.solution_view();
+        let language = view.language();
+        let watch = view.watch_status();
+        let env = view.env_view();
+        let working_dir = view.working_dir_view();
+        let (compile_in_shell, run_in_shell) = view.shell_modes();
+        let generator = view.generator_view();
+        let brute_force = view.brute_force_view();
+        let differential = view.differential_status();
         let selected = view.curr_field();
+        let compilation = view.compilation_status();
         Self {
             selected,
+            compilation,
             compile_command,
             run_command,
+            solution,
+            language,
+            watch,
+            env,
+            working_dir,
+            compile_in_shell,
+            run_in_shell,
+            generator,
+            brute_force,
+            differential,
         }
     }
 
     fn render<B: Backend>(self, frame: &mut Frame<B>, layout: &ProblemMenuLayout) {
         let bold_style = Style::default().add_modifier(Modifier::BOLD);
 
-        let (compile_style, run_style) = match &self.selected {
-            Some(InputField::RunCommand) => (Style::default(), bold_style),
-            Some(InputField::CompileCommand) => (bold_style, Style::default()),
-            None => (Style::default(), Style::default()),
+        let style_of = |field| match self.selected {
+            Some(selected) if selected == field => bold_style,
+            _ => Style::default(),
+        };
+        let compile_style = style_of(InputField::CompileCommand);
+        let run_style = style_of(InputField::RunCommand);
+        let language = self.language.as_deref().unwrap_or("custom");
+        let yes_no = |flag| if flag { "yes" } else { "no" };
+        let working_dir = match self.working_dir.as_str() {
+            "" => "current directory",
+            dir => dir,
         };
 
-        let commands = Paragraph::new(vec![
+        let mut lines = vec![
+            Spans::from(Span::styled(
+                "[F]ile: ".to_string() + &self.solution,
+                style_of(InputField::SolutionPath),
+            )),
+            Spans::from(format!("Language (←/→): {}", language)),
+            Spans::from(format!("[W]atch: {}", self.watch)),
             Spans::from(Span::styled(
                 "[C]ompile command: ".to_string() + &self.compile_command,
                 compile_style,
@@ -48,7 +93,35 @@ impl UIElement for CommandsView {
                 "[R]un Command: ".to_string() + &self.run_command,
                 run_style,
             )),
-        ]);
+            Spans::from(format!(
+                "Shell: compile [X]: {}, run [Y]: {}",
+                yes_no(self.compile_in_shell),
+                yes_no(self.run_in_shell)
+            )),
+            Spans::from(Span::styled(
+                "En[v]ironment: ".to_string() + &self.env,
+                style_of(InputField::Environment),
+            )),
+            Spans::from(Span::styled(
+                "Wor[k]ing directory: ".to_string() + working_dir,
+                style_of(InputField::WorkingDir),
+            )),
+            Spans::from(Span::styled(
+                "[G]enerator: ".to_string() + &self.generator,
+                style_of(InputField::Generator),
+            )),
+            Spans::from(Span::styled(
+                "[B]rute force: ".to_string() + &self.brute_force,
+                style_of(InputField::BruteForce),
+            )),
+            Spans::from(""),
+        ];
+        lines.append(&mut self.compilation.into_detailed());
+        if let Some(differential) = self.differential {
+            lines.append(&mut differential.into_detailed());
+        }
+
+        let commands = Paragraph::new(lines).wrap(Wrap { trim: false });
 
         frame.render_widget(commands, layout.problem_tabs);
     }
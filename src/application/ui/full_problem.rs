@@ -10,6 +10,7 @@ use tui::widgets::Borders;
 use tui::Frame;
 use tui::widgets::Paragraph;
 
+#[allow(clippy::enum_variant_names)]
 enum ProblemTabs {
     TestCaseTab(TestCaseTable),
     DetailsTab(TestCaseDetails),
@@ -86,8 +87,8 @@ impl<'a> UIElement for FullProblem<'a> {
 
 #[memoize::memoize]
 fn problem_menu_help(_unit: ()) -> Paragraph<'static> {
-    return Paragraph::new("q - quit,   h - help,   press keys in [b]rackets to use menu items,  use arrows to navigate problem details")
-        .alignment(tui::layout::Alignment::Center);
+    Paragraph::new("q - quit,   h - help,   press keys in [b]rackets to use menu items,  use arrows to navigate problem details")
+        .alignment(tui::layout::Alignment::Center)
 }
 
 #[memoize::memoize]
@@ -39,7 +39,19 @@ fn create_solve_help(_unit: ()) -> Paragraph<'static> {
         Spans::from(bold("When solving problem")),
         Spans::from("  c - edit compile script"),
         Spans::from("  r - edit run script"),
+        Spans::from("  f - edit path to the solution"),
+        Spans::from("  x/y - run the compile/run script through the shell"),
+        Spans::from("  v - edit environment variables (KEY=VALUE ...)"),
+        Spans::from("  k - edit working directory"),
+        Spans::from("  g/b - edit generator/brute force command"),
+        Spans::from("  m - compare with brute force on generated inputs"),
+        Spans::from("  left/right - switch language (in the setup tab)"),
+        Spans::from("  w - re-run test cases whenever the solution changes"),
         Spans::from("  enter - run all test cases"),
+        Spans::from("  a - run again the test cases which did not pass"),
+        Spans::from("  o - run only the test case selected in details"),
+        Spans::from("  n - shrink the input of the test case selected in details"),
+        Spans::from("  e - run only the examples, without stress tests"),
         Spans::from("  backspace - cancel running test cases"),
         Spans::from("  t - see status of test cases"),
         Spans::from("  s - see run/compile scripts"),
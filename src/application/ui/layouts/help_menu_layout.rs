@@ -10,6 +10,7 @@ const SPLIT_HELP: [Constraint; 3] = [
 
 #[derive(Clone, Copy)]
 pub struct HelpScreenLayout {
+    #[allow(dead_code)]
     pub outer_window: Rect,
     pub general_help: Rect,
     pub select_help: Rect,
@@ -1,5 +1,6 @@
 use tui::widgets::Axis;
 use tui::widgets::Chart;
+use crate::application::common::Clock;
 use crate::application::ui::ProblemMenuLayout;
 use crate::application::ui::UIElement;
 use crate::application::View;
@@ -14,6 +15,9 @@ use tui::{
 
 pub struct PerformanceChart {
     data: Vec<(f64, f64)>,
+    timed_out: Vec<(f64, f64)>,
+    error_bars: Vec<[(f64, f64); 2]>,
+    clock: Clock,
 }
 
 impl UIElement for PerformanceChart {
@@ -21,15 +25,21 @@ impl UIElement for PerformanceChart {
 
     fn setup(view: &View) -> Self {
         let data = view.performance();
+        let timed_out = view.timed_out_performance();
+        let error_bars = view.performance_error_bars();
         Self {
-            data
+            data,
+            timed_out,
+            error_bars,
+            clock: view.clock(),
         }
     }
 
     fn render<B: Backend>(self, frame: &mut Frame<B>, layout: &ProblemMenuLayout) {
         let points = self.data;
+        let timed_out = self.timed_out;
 
-        if points.is_empty() {
+        if points.is_empty() && timed_out.is_empty() {
             return;
         }
 
@@ -39,22 +49,55 @@ impl UIElement for PerformanceChart {
             .graph_type(GraphType::Line)
             .data(&points);
 
-        let min_complexity = points[0].0;
-        let max_complexity = points[points.len() - 1].0;
+        let timed_out_dataset = Dataset::default()
+            .name("time limit")
+            .marker(symbols::Marker::Dot)
+            .style(Style::default().fg(Color::Red))
+            .graph_type(GraphType::Scatter)
+            .data(&timed_out);
 
-        let max_time: f64 = points.iter()
+        let error_bars = self.error_bars;
+        let error_bar_datasets = error_bars.iter().map(|bar| {
+            Dataset::default()
+                .marker(symbols::Marker::Braille)
+                .style(Style::default().fg(Color::Gray))
+                .graph_type(GraphType::Line)
+                .data(bar)
+        });
+
+        let all_points = || points.iter().chain(timed_out.iter()).chain(error_bars.iter().flatten());
+
+        let min_complexity = all_points()
+            .map(|(x, _)| *x)
+            .min_by(|a, b| a.total_cmp(b))
+            .unwrap_or(0.0);
+        let max_complexity = all_points()
+            .map(|(x, _)| *x)
+            .max_by(|a, b| a.total_cmp(b))
+            .unwrap_or(1.0);
+
+        let max_time: f64 = all_points()
             .map(|(_, y)| y)
             .max_by(|a, b| a.total_cmp(b))
             .cloned()
             .unwrap_or(1.0) * 1.1;
 
-        let chart = Chart::new(vec![dataset])
+        let y_title = match self.clock {
+            Clock::Wall => "Wall time[s]",
+            Clock::Cpu => "CPU time[s]",
+        };
+
+        let mut datasets: Vec<Dataset> = error_bar_datasets.collect();
+        datasets.push(dataset);
+        datasets.push(timed_out_dataset);
+
+        let chart = Chart::new(datasets)
             .x_axis(Axis::default().title("log_2(Complexity)").bounds([min_complexity, max_complexity]).labels(vec![
                 Span::from(format!("{:.2}", min_complexity)),
                 Span::from(format!("{:.2}", ((min_complexity + max_complexity) / 2.0))),
                 Span::from(format!("{:.2}", max_complexity)),
             ]))
-            .y_axis(Axis::default().title("Time[s]").bounds([0.0, max_time]).labels(vec![
+            .y_axis(Axis::default().title(y_title).bounds([0.0, max_time]).labels(vec![
                 Span::from("0.00"),
                 Span::from(format!("{:.2}", max_time))
             ]));
@@ -39,8 +39,18 @@ impl<'a> UIElement for ProblemView<'a> {
     type ExpectedLayout = ProblemStatementLayout;
 
     fn setup(view: &View) -> Self {
-        let problem = view.current_problem();
-        let fst_example = problem.test_cases.get(0).unwrap();
+        let problem = match view.current_problem() {
+            Some(problem) => problem,
+            None => {
+                return Self {
+                    title: Paragraph::new(Spans::from(bold("No problems were loaded".to_string())))
+                        .alignment(tui::layout::Alignment::Center),
+                    statement: Paragraph::new(""),
+                    example: Paragraph::new(""),
+                }
+            }
+        };
+        let fst_example = problem.test_cases.first().unwrap();
         let title = Paragraph::new(Spans::from(bold(problem.name.clone())))
             .alignment(tui::layout::Alignment::Center);
         let statement = Paragraph::new(Spans::from(text(problem.statement.clone())))
@@ -2,7 +2,7 @@ use crate::application::common::*;
 use crate::application::ui::ProblemMenuLayout;
 use crate::application::ui::UIElement;
 use crate::application::View;
-use tui::widgets::Paragraph;
+use tui::widgets::{Paragraph, Wrap};
 use tui::{
     backend::Backend,
     layout::Alignment,
@@ -26,7 +26,11 @@ fn pretty_bar(curr: usize, max: usize) -> Spans<'static> {
 }
 
 pub struct TestCaseDetails {
+    compilation: CompilationStatus,
     test_case: TestCaseStatus,
+    transcript: Option<String>,
+    ad_hoc_input: Option<String>,
+    shrink: Option<ShrinkStatus>,
     id: usize,
     total: usize,
 }
@@ -37,7 +41,15 @@ impl UIElement for TestCaseDetails {
     fn setup(view: &View) -> Self {
         let (id, test_case) = view.details_for_selected_test_case();
         let total = view.number_of_tests();
+        let compilation = view.compilation_status();
+        let transcript = view.transcript_for_selected_test_case();
+        let ad_hoc_input = view.ad_hoc_input_for_selected_test_case();
+        let shrink = view.shrink_status();
         Self {
+            shrink,
+            transcript,
+            ad_hoc_input,
+            compilation,
             id,
             test_case,
             total,
@@ -45,14 +57,36 @@ impl UIElement for TestCaseDetails {
     }
 
     fn render<B: Backend>(self, frame: &mut Frame<B>, layout: &ProblemMenuLayout) {
+        if let CompilationStatus::CompilationError { .. } = self.compilation {
+            let widget = Paragraph::new(self.compilation.into_detailed()).wrap(Wrap { trim: false });
+            frame.render_widget(widget, layout.problem_tabs);
+            return;
+        }
+
         let mut lines = vec![
             Spans::from(format!("View of test case {} / {}:", self.id, self.total)),
             Spans::from(""),
         ];
+        if let Some(shrink) = self.shrink {
+            lines.append(&mut shrink.into_detailed());
+            lines.push(Spans::from(""));
+        }
         let mut details = self.test_case.into_detailed();
         lines.append(&mut details);
 
-        let widget = Paragraph::new(lines);
+        if let Some(input) = self.ad_hoc_input {
+            lines.push(Spans::from(""));
+            lines.push(Spans::from("Input:"));
+            lines.extend(input.lines().map(|line| Spans::from(line.to_string())));
+        }
+
+        if let Some(transcript) = self.transcript {
+            lines.push(Spans::from(""));
+            lines.push(Spans::from("Transcript of the interaction:"));
+            lines.extend(transcript.lines().map(|line| Spans::from(line.to_string())));
+        }
+
+        let widget = Paragraph::new(lines).wrap(Wrap { trim: false });
         frame.render_widget(widget, layout.problem_tabs);
         frame.render_widget(
             Paragraph::new(pretty_bar(self.id, self.total)).alignment(Alignment::Right),
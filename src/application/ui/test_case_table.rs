@@ -4,18 +4,26 @@ use crate::application::ui::UIElement;
 use crate::application::View;
 use tui::{
     backend::Backend,
-    layout::Constraint,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Style},
     text::{Span, Spans},
-    widgets::{Cell, Row, Table},
+    widgets::{Cell, Paragraph, Row, Table, Wrap},
     Frame,
 };
 
-fn add_number(n: usize, text: Span) -> Spans {
-    Spans::from(vec![Span::from(format!("{}. ", n)), text])
+fn describe(n: usize, status: TestCaseStatus, clock: Clock) -> Spans<'static> {
+    let usage = status.usage(clock).map(|usage| format!(" ({})", usage)).unwrap_or_default();
+    Spans::from(vec![
+        Span::from(format!("{}. ", n)),
+        status.into_span(),
+        Span::from(usage),
+    ])
 }
 
 pub struct TestCaseTable {
     test_cases: Vec<TestCaseStatus>,
+    clock: Clock,
+    stress_test_error: Option<String>,
 }
 
 impl UIElement for TestCaseTable {
@@ -23,16 +31,17 @@ impl UIElement for TestCaseTable {
 
     fn setup(view: &View) -> Self {
         let test_cases = view.get_test_cases();
-        Self { test_cases }
+        let clock = view.clock();
+        let stress_test_error = view.stress_test_error();
+        Self { test_cases, clock, stress_test_error }
     }
 
     fn render<B: Backend>(self, frame: &mut Frame<B>, layout: &ProblemMenuLayout) {
         let display_statuses = self
             .test_cases
             .into_iter()
-            .map(TestCaseStatus::into_span)
             .enumerate()
-            .map(|(n, text)| add_number(n, text))
+            .map(|(n, status)| describe(n, status, self.clock))
             .map(Cell::from)
             .enumerate(); // Stupid and slow :(
 
@@ -49,12 +58,29 @@ impl UIElement for TestCaseTable {
             }
         }
 
-        let constraints = vec![Constraint::Length(15); 12];
+        let constraints = vec![Constraint::Length(32); 12];
 
         let test_case_data = Table::new([Row::new(row_1), Row::new(row_2), Row::new(row_3)])
             .column_spacing(3)
             .widths(&constraints);
 
-        frame.render_widget(test_case_data, layout.problem_tabs);
+        let area = match &self.stress_test_error {
+            Some(err_msg) => {
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(1), Constraint::Length(3)])
+                    .split(layout.problem_tabs);
+                let error = Paragraph::new(Span::styled(
+                    format!("Stress tests could not be generated: {}", err_msg),
+                    Style::default().fg(Color::Red),
+                ))
+                .wrap(Wrap { trim: false });
+                frame.render_widget(error, chunks[1]);
+                chunks[0]
+            }
+            None => layout.problem_tabs,
+        };
+
+        frame.render_widget(test_case_data, area);
     }
 }
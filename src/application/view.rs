@@ -34,6 +34,11 @@ impl View {
         sign.to_string()
     }
 
+    /// Time used for the time limits and the performance chart
+    pub fn clock(&self) -> Clock {
+        self.model.settings.borrow().clock
+    }
+
     pub fn curr_data(&self) -> ProblemDataTab {
         self.model.problem_data_tab.get()
     }
@@ -42,10 +47,15 @@ impl View {
         self.model.current_menu.get()
     }
 
-    pub fn current_problem(&self) -> Rc<Problem> {
+    pub fn current_problem(&self) -> Option<Rc<Problem>> {
         self.model.current_problem()
     }
 
+    /// Why some of the sources of problems could not be loaded
+    pub fn source_errors(&self) -> Vec<String> {
+        self.model.get_source_errors()
+    }
+
     pub fn curr_field(&self) -> Option<InputField> {
         self.model.input_handler.current_field()
     }
@@ -66,14 +76,85 @@ impl View {
         self.model.get_field(InputField::RunCommand)
     }
 
+    pub fn solution_view(&self) -> String {
+        if self.model.input_handler.is_in_input_mode() && self.model.input_handler.current_field() == Some(InputField::SolutionPath) {
+            return self.model.input_handler.curr_input() + "|";
+        }
+
+        self.model.get_field(InputField::SolutionPath)
+    }
+
+    pub fn env_view(&self) -> String {
+        self.field_view(InputField::Environment)
+    }
+
+    pub fn working_dir_view(&self) -> String {
+        self.field_view(InputField::WorkingDir)
+    }
+
+    /// Value of the field, with the cursor if it is being edited.
+    fn field_view(&self, field: InputField) -> String {
+        if self.model.input_handler.is_in_input_mode() && self.model.input_handler.current_field() == Some(field) {
+            return self.model.input_handler.curr_input() + "|";
+        }
+
+        self.model.get_field(field)
+    }
+
+    /// Whether the compile and the run step are run through a shell.
+    pub fn shell_modes(&self) -> (bool, bool) {
+        let settings = self.model.settings.borrow();
+        (settings.compile_in_shell, settings.run_in_shell)
+    }
+
+    pub fn watch_status(&self) -> String {
+        self.model.watch_status()
+    }
+
+    pub fn language(&self) -> Option<String> {
+        self.model.settings.borrow().language.clone()
+    }
+
     pub fn get_test_cases(&self) -> Vec<TestCaseStatus> {
         self.model.get_test_cases()
     }
 
+    pub fn stress_test_error(&self) -> Option<String> {
+        self.model.get_stress_test_error()
+    }
+
+    pub fn differential_status(&self) -> Option<DifferentialStatus> {
+        self.model.get_differential_status()
+    }
+
+    pub fn shrink_status(&self) -> Option<ShrinkStatus> {
+        self.model.get_shrink_status()
+    }
+
+    pub fn ad_hoc_input_for_selected_test_case(&self) -> Option<String> {
+        self.model.ad_hoc_input_for_selected_test_case()
+    }
+
+    pub fn generator_view(&self) -> String {
+        self.field_view(InputField::Generator)
+    }
+
+    pub fn brute_force_view(&self) -> String {
+        self.field_view(InputField::BruteForce)
+    }
+
+    pub fn compilation_status(&self) -> CompilationStatus {
+        self.model.get_compilation_status()
+    }
+
     pub fn details_for_selected_test_case(&self) -> (usize, TestCaseStatus) {
         self.model.details_for_selected_test_case()
     }
 
+    pub fn transcript_for_selected_test_case(&self) -> Option<String> {
+        self.model.transcript_for_selected_test_case()
+    }
+
     pub fn check_for_changes(&self) -> bool {
         self.model.check_for_changes()
     }
@@ -84,11 +165,13 @@ impl View {
 
     pub fn performance(&self) -> Vec<(f64, f64)> {
         let test_cases = self.model.get_test_cases();
+        let clock = self.clock();
 
         let mut points: Vec<(f64, f64)> = test_cases.into_iter()
             .map(|tc| match tc {
-                TestCaseStatus::Pass { time, complexity } => Some((complexity as f64, time.as_secs_f64())),
-                TestCaseStatus::Fail { expected: _, actual: _, time, complexity } => Some((complexity as f64, time.as_secs_f64())),
+                TestCaseStatus::Pass { stats: Some(stats), complexity, .. } => Some((complexity as f64, stats.median.as_secs_f64())),
+                TestCaseStatus::Pass { time, complexity, .. } => Some((complexity as f64, time.get(clock).as_secs_f64())),
+                TestCaseStatus::Fail { time, complexity, .. } => Some((complexity as f64, time.get(clock).as_secs_f64())),
                 _ => None
             })
             .filter(Option::is_some)
@@ -100,4 +183,33 @@ impl View {
 
         points
     }
+
+    /// Error bars spanning one standard deviation around the median of
+    /// benchmarked test cases, as pairs of their bottom and top points.
+    pub fn performance_error_bars(&self) -> Vec<[(f64, f64); 2]> {
+        self.model.get_test_cases().into_iter()
+            .filter_map(|tc| match tc {
+                TestCaseStatus::Pass { stats: Some(stats), complexity, .. } => Some((complexity as f64, stats)),
+                _ => None
+            })
+            .map(|(n, stats)| {
+                let x = n.log(2.0);
+                let median = stats.median.as_secs_f64();
+                let stddev = stats.stddev.as_secs_f64();
+                [(x, (median - stddev).max(0.0)), (x, median + stddev)]
+            })
+            .collect()
+    }
+
+    /// Test cases which were killed after exceeding the time limit. Their
+    /// time is capped at the limit.
+    pub fn timed_out_performance(&self) -> Vec<(f64, f64)> {
+        self.model.get_test_cases().into_iter()
+            .filter_map(|tc| match tc {
+                TestCaseStatus::TimeLimitExceeded { limit, elapsed: _, complexity } => Some((complexity as f64, limit.as_secs_f64())),
+                _ => None
+            })
+            .map(|(n, t)| (n.log(2.0), t))
+            .collect()
+    }
 }
@@ -1,3 +1,5 @@
+use crate::application::common::Clock;
+use crate::application::language_profile::{LanguageProfile, PROFILES};
 use clap::Parser;
 
 #[derive(Parser, Debug)]
@@ -19,24 +21,106 @@ pub struct AppArgs {
     #[clap(long, action, default_value_t = false)]
     pub disable_unicode: bool,
 
-    /// Path to yaml file with all problems, can be a web link
+    /// Path to yaml file with problems, can be a web link. Can also be a
+    /// directory of problem packages. Can be repeated to merge several of
+    /// them. Defaults to `default`, the problems downloaded by `update` or the
+    /// ones built into the app
     #[clap(long, value_parser)]
-    pub db_path: Option<String>,
+    pub db_path: Vec<String>,
+
+    /// Path to the solution. Picks the language profile from its extension
+    #[clap(long, value_parser)]
+    pub solution: Option<String>,
+
+    /// Language profile providing the compile and run commands: Rust, C++, C,
+    /// Python, Java, Go or Haskell
+    #[clap(long, value_parser = language_name)]
+    pub language: Option<String>,
 
     #[clap(long, value_parser)]
     pub compilation_step: Option<String>,
 
+    /// File or directory the compile step depends on, can be repeated.
+    /// Compilation is skipped if neither it nor the solution changed
+    #[clap(long, value_parser)]
+    pub compile_input: Vec<String>,
+
     #[clap(long, value_parser)]
     pub run_step: Option<String>,
+
+    /// Run the compile step through `sh -c`, which allows pipes and `&&`
+    #[clap(long, action, default_value_t = false)]
+    pub compile_in_shell: bool,
+
+    /// Run the run step through `sh -c`, which allows pipes and `&&`
+    #[clap(long, action, default_value_t = false)]
+    pub run_in_shell: bool,
+
+    /// Environment variable of the compile and run steps as KEY=VALUE, can be repeated
+    #[clap(long, value_parser)]
+    pub env: Vec<String>,
+
+    /// Directory the compile and run steps are started in
+    #[clap(long, value_parser)]
+    pub working_dir: Option<String>,
+
+    /// Command printing a random input for differential testing. Gets the
+    /// seed as its last argument
+    #[clap(long, value_parser)]
+    pub generator: Option<String>,
+
+    /// Slow but trusted solution, which the solution is compared with on
+    /// generated inputs
+    #[clap(long, value_parser)]
+    pub brute_force: Option<String>,
+
+    /// Number of test cases to run in parallel. Stress tests always run one at a time
+    #[clap(long, short, value_parser)]
+    pub jobs: Option<usize>,
+
+    /// Keep inputs and outputs of every run in this directory instead of removing them
+    #[clap(long, value_parser)]
+    pub keep_artifacts: Option<String>,
+
+    /// Run solutions without network access, with a read-only filesystem
+    /// and without dangerous system calls
+    #[clap(long, action, default_value_t = false)]
+    pub sandbox: bool,
+
+    /// Time used for the time limits and the performance chart
+    #[clap(long, value_enum)]
+    pub clock: Option<Clock>,
+
+    /// Run every stress test this many times and show statistics of the times
+    #[clap(long, value_parser)]
+    pub benchmark_runs: Option<usize>,
+
+    /// Run every stress test this many times before the measured runs
+    #[clap(long, value_parser)]
+    pub warmup_runs: Option<usize>,
+}
+
+/// Name of a known language profile, in any case.
+fn language_name(name: &str) -> Result<String, String> {
+    match LanguageProfile::by_name(name) {
+        Some(profile) => Ok(profile.name.to_string()),
+        None => {
+            let known: Vec<&str> = PROFILES.iter().map(|profile| profile.name).collect();
+            Err(format!("unknown language profile, expected one of: {}", known.join(", ")))
+        }
+    }
 }
 
 #[derive(clap::Subcommand, Debug)]
 pub enum Action {
-    /// Gets list of problems from github and populates db
+    /// Downloads the latest problems from github into the local cache
     Update,
 
     /// Runs the terminal UI
     Run,
+
+    /// Checks the problems and reports everything wrong with them
+    Validate,
 }
 
 #[derive(clap::Args, Debug)]
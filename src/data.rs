@@ -1,91 +1,198 @@
+use std::path::Path;
 use std::rc::Rc;
 use crate::application::common::Problem;
 use crate::application::model::Db;
-use crate::application::common::TestCase;
+use crate::application::settings::DEFAULT_SOURCE;
 
-pub fn load(path: &str) -> Db {
-    if is_web_link(path) {
-        load_from_web(path)
+mod cache;
+mod package;
+mod validation;
+
+pub use cache::{update, Update};
+pub use validation::Issue;
+
+const DOWNLOAD_TIMEOUT_S: u64 = 30;
+
+/// Loads problems from every source into one catalogue. Every problem
+/// gets the namespace of its source, so problems with the same name can be
+/// told apart. Sources which cannot be loaded are skipped, with every issue
+/// found in them returned instead.
+pub fn load(sources: &[String]) -> (Db, Vec<String>) {
+    let mut db = Vec::new();
+    let mut errors = Vec::new();
+    let mut namespaces = Vec::new();
+
+    for source in sources {
+        let namespace = unique_namespace(source, &namespaces);
+        namespaces.push(namespace.clone());
+        match load_problems(source) {
+            Ok(problems) => db.extend(problems.into_iter().map(|problem| {
+                Rc::new(Problem {
+                    source: namespace.clone(),
+                    ..problem
+                })
+            })),
+            Err(issues) => {
+                let issues: Vec<String> = issues.iter().map(ToString::to_string).collect();
+                errors.push(format!("Failed to load {}:\n{}", source, issues.join("\n")));
+            }
+        }
+    }
+
+    (db, errors)
+}
+
+/// Checks the problems of a source without keeping them. Returns how many
+/// there are.
+pub fn validate(source: &str) -> Result<usize, Vec<Issue>> {
+    load_problems(source).map(|problems| problems.len())
+}
+
+/// Loads problems from a YAML file, a web link to one, a directory of
+/// problem packages, or the default source.
+fn load_problems(source: &str) -> Result<Vec<Problem>, Vec<Issue>> {
+    if source == DEFAULT_SOURCE {
+        cache::load()
+    } else if is_web_link(source) {
+        let serialized = fetch(source).map_err(|err| vec![Issue::new(source, err)])?;
+        validation::problems(source, &serialized)
+    } else if Path::new(source).is_dir() {
+        package::load_packages(Path::new(source))
     } else {
-        load_from_file(path)
+        let serialized = std::fs::read_to_string(source)
+            .map_err(|err| vec![Issue::new(source, format!("Cannot read the file: {}", err))])?;
+        validation::problems(source, &serialized)
+    }
+}
+
+/// Name of the file or directory of the source, without the extension. A
+/// number is added if another source already has it.
+fn unique_namespace(source: &str, taken: &[String]) -> String {
+    let name = match is_web_link(source) || source == DEFAULT_SOURCE {
+        true => source.trim_end_matches('/').rsplit('/').next().map(str::to_string),
+        false => std::path::absolute(source)
+            .ok()
+            .and_then(|path| path.file_name().map(|name| name.to_string_lossy().into_owned())),
+    };
+    let name = name.unwrap_or_default();
+    let name = match name.split_once('.') {
+        Some((stem, _)) if !stem.is_empty() => stem.to_string(),
+        _ => name,
+    };
+
+    let mut namespace = name.clone();
+    let mut n = 1;
+    while taken.contains(&namespace) {
+        n += 1;
+        namespace = format!("{}-{}", name, n);
     }
+    namespace
 }
 
 fn is_web_link(text: &str) -> bool {
     text.starts_with("http")
 }
 
-fn load_from_web(link: &str) -> Db {
-    let response = minreq::get(link).send().unwrap();
-    let serialized = response.as_str().unwrap();
-    let owned: Vec<Problem> = serde_yaml::from_str(serialized).unwrap();
-    owned.into_iter().map(Rc::new).collect()
+fn fetch(link: &str) -> Result<String, String> {
+    let response = minreq::get(link)
+        .with_timeout(DOWNLOAD_TIMEOUT_S)
+        .send()
+        .map_err(|err| err.to_string())?;
+    if response.status_code != 200 {
+        return Err(format!("Download failed with status {} {}", response.status_code, response.reason_phrase));
+    }
+    response.as_str().map(str::to_string).map_err(|err| err.to_string())
 }
 
-fn load_from_file(path: &str) -> Db {
-    let serialized =
-        std::fs::read_to_string(path).expect("Something went wrong reading the file");
-    let problems: Vec<Problem> = serde_yaml::from_str(&serialized).unwrap();
-    problems.into_iter().map(Rc::new).collect()
-}
+/// Directories for the tests to write problems to.
+#[cfg(test)]
+mod testing {
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Fresh directory, removed with everything inside when dropped.
+    pub struct TempDir(PathBuf);
+
+    impl TempDir {
+        pub fn new() -> Self {
+            static COUNT: AtomicUsize = AtomicUsize::new(0);
+            let name = format!("algonds-data-test-{}-{}", std::process::id(), COUNT.fetch_add(1, Ordering::Relaxed));
+            let path = std::env::temp_dir().join(name);
+            std::fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
 
+        pub fn path(&self) -> &Path {
+            &self.0
+        }
 
-pub fn generate_stress_tests_for(problem_name: &str, last_id: usize) -> Vec<TestCase> {
-    match problem_name {
-        "Print \"Hello, world!\" (tutorial)" => stress_tests_for_hello_world(last_id),
-        "Longest common substring" => stress_tests_for_lcs(last_id),
-        _ => vec![]
+        /// Writes `contents` to `file` inside, creating the directories on
+        /// the way.
+        pub fn write(&self, file: &str, contents: &str) -> PathBuf {
+            let path = self.0.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, contents).unwrap();
+            path
+        }
     }
-}
 
-fn stress_tests_for_lcs(last_id: usize) -> Vec<TestCase> {
-    vec![
-        TestCase {
-            id: last_id + 1,
-            complexity: 100 * 6,
-            input: "abcde".repeat(100) + " " + &"abcdez".repeat(100),
-            output: "abcde".to_string(),
-            is_stress_test: true,
-        },
-        TestCase {
-            id: last_id + 2,
-            complexity: 1000 * 6,
-            input: "abcde".repeat(1000) + " " + &"abcdez".repeat(1000),
-            output: "abcde".to_string(),
-            is_stress_test: true,
-        },
-        TestCase {
-            id: last_id + 3,
-            complexity: 10_000 * 6,
-            input: "abcde".repeat(10_000) + " " + &"abcdez".repeat(10_000),
-            output: "abcde".to_string(),
-            is_stress_test: true,
-        },
-    ]
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
 }
 
-fn stress_tests_for_hello_world(last_id: usize) -> Vec<TestCase> {
-    vec![
-        TestCase {
-            id: last_id + 1,
-            complexity: 100,
-            input: "a".repeat(100),
-            output: "Hello, world!".to_string(),
-            is_stress_test: true,
-        },
-        TestCase {
-            id: last_id + 2,
-            complexity: 1000,
-            input: "a".repeat(1000),
-            output: "Hello, world!".to_string(),
-            is_stress_test: true,
-        },
-        TestCase {
-            id: last_id + 3,
-            complexity: 10000,
-            input: "a".repeat(10000),
-            output: "Hello, world!".to_string(),
-            is_stress_test: true,
-        }
-    ]
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::testing::TempDir;
+    use super::*;
+
+    const PROBLEMS: &str = "- name: Sum\n  statement: Add\n  difficulty: Easy\n  test_cases:\n    - id: 0\n      complexity: 1\n      input: '1 2'\n      output: '3'\n      is_stress_test: false\n";
+
+    fn path(path: &Path) -> String {
+        path.display().to_string()
+    }
+
+    #[test]
+    fn namespaces_come_from_the_file_names() {
+        assert_eq!(unique_namespace("dir/contest.yaml", &[]), "contest");
+        assert_eq!(unique_namespace("dir/problems/", &[]), "problems");
+        assert_eq!(unique_namespace("https://example.com/set/db.yaml", &[]), "db");
+        assert_eq!(unique_namespace(".hidden", &[]), ".hidden");
+    }
+
+    #[test]
+    fn taken_namespaces_get_a_number() {
+        let taken = vec!["db".to_string(), "db-2".to_string()];
+        assert_eq!(unique_namespace("other/db.yaml", &taken[..1]), "db-2");
+        assert_eq!(unique_namespace("other/db.yaml", &taken), "db-3");
+    }
+
+    #[test]
+    fn sources_are_merged_with_their_namespaces() {
+        let first = TempDir::new();
+        let second = TempDir::new();
+        let sources = [
+            path(&first.write("contest.yaml", PROBLEMS)),
+            path(&second.write("contest.yaml", PROBLEMS)),
+        ];
+
+        let (db, errors) = load(&sources);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let problems: Vec<(&str, &str)> = db.iter().map(|p| (p.source.as_str(), p.name.as_str())).collect();
+        assert_eq!(problems, [("contest", "Sum"), ("contest-2", "Sum")]);
+    }
+
+    #[test]
+    fn broken_source_is_skipped_with_its_issues() {
+        let dir = TempDir::new();
+        let sources = [path(&dir.write("broken.yaml", "- name: [")), path(&dir.write("good.yaml", PROBLEMS))];
+
+        let (db, errors) = load(&sources);
+        assert_eq!(db.len(), 1);
+        assert_eq!(db[0].source, "good");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with(&format!("Failed to load {}:\n", sources[0])), "{}", errors[0]);
+    }
+}
@@ -0,0 +1,223 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::validation::{self, Issue};
+use super::DOWNLOAD_TIMEOUT_S;
+use crate::application::common::Problem;
+
+/// Where `update` downloads the problems from.
+const DB_ADDR: &str = "https://raw.githubusercontent.com/MaciejWas/algonds/main/src/data/db.yaml";
+
+/// Problems shipped with the app, used until `update` is run.
+const EMBEDDED_DB: &str = include_str!("db.yaml");
+const EMBEDDED_NAME: &str = "<embedded db.yaml>";
+
+const DB_FILE: &str = "db.yaml";
+const INFO_FILE: &str = "db.info.yaml";
+
+/// How the cached problems were downloaded, so that the next `update` only
+/// downloads them if they have changed.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheInfo {
+    url: String,
+    #[serde(default)]
+    etag: Option<String>,
+    #[serde(default)]
+    last_modified: Option<String>,
+}
+
+/// Outcome of `update`.
+pub enum Update {
+    /// Number of downloaded problems
+    Downloaded(usize),
+    UpToDate,
+}
+
+/// Directory of the cache, `$XDG_DATA_HOME/algonds` or
+/// `~/.local/share/algonds`.
+fn cache_dir() -> Result<PathBuf, String> {
+    let data_home = match std::env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => match std::env::var_os("HOME").filter(|dir| !dir.is_empty()) {
+            Some(home) => Path::new(&home).join(".local").join("share"),
+            None => return Err("Neither XDG_DATA_HOME nor HOME is set".to_string()),
+        },
+    };
+    Ok(data_home.join("algonds"))
+}
+
+/// Problems from the cache, or the embedded ones if nothing is cached.
+pub fn load() -> Result<Vec<Problem>, Vec<Issue>> {
+    let db_path = cache_dir().map(|dir| dir.join(DB_FILE));
+    match db_path {
+        Ok(db_path) if db_path.is_file() => {
+            let file = db_path.display().to_string();
+            let serialized = std::fs::read_to_string(&db_path)
+                .map_err(|err| vec![Issue::new(&file, format!("Cannot read the file: {}", err))])?;
+            validation::problems(&file, &serialized)
+        }
+        _ => validation::problems(EMBEDDED_NAME, EMBEDDED_DB),
+    }
+}
+
+/// Downloads the problems into the cache, unless they have not changed
+/// since the last time. They are checked first, and the cached file is
+/// replaced at once, so that it is never left half written.
+pub fn update() -> Result<Update, String> {
+    update_from(DB_ADDR, &cache_dir()?)
+}
+
+fn update_from(url: &str, dir: &Path) -> Result<Update, String> {
+    let db_path = dir.join(DB_FILE);
+    let info_path = dir.join(INFO_FILE);
+
+    let info: CacheInfo = std::fs::read_to_string(&info_path)
+        .ok()
+        .and_then(|info| serde_yaml::from_str(&info).ok())
+        .filter(|info: &CacheInfo| info.url == url && db_path.is_file())
+        .unwrap_or_default();
+
+    let mut request = minreq::get(url).with_timeout(DOWNLOAD_TIMEOUT_S);
+    if let Some(etag) = &info.etag {
+        request = request.with_header("If-None-Match", etag.as_str());
+    }
+    if let Some(last_modified) = &info.last_modified {
+        request = request.with_header("If-Modified-Since", last_modified.as_str());
+    }
+    let response = request.send().map_err(|err| format!("Failed to download {}: {}", url, err))?;
+
+    match response.status_code {
+        304 => return Ok(Update::UpToDate),
+        200 => {}
+        code => return Err(format!("Failed to download {}: {} {}", url, code, response.reason_phrase)),
+    }
+
+    let serialized = response.as_str().map_err(|err| err.to_string())?;
+    let problems = validation::problems(url, serialized).map_err(|issues| {
+        let issues: Vec<String> = issues.iter().map(ToString::to_string).collect();
+        format!("Downloaded problems are not valid:\n{}", issues.join("\n"))
+    })?;
+
+    std::fs::create_dir_all(dir).map_err(|err| format!("Cannot create {}: {}", dir.display(), err))?;
+    write_atomically(&db_path, serialized.as_bytes())?;
+
+    let info = CacheInfo {
+        url: url.to_string(),
+        etag: response.headers.get("etag").cloned(),
+        last_modified: response.headers.get("last-modified").cloned(),
+    };
+    let info = serde_yaml::to_string(&info).map_err(|err| err.to_string())?;
+    write_atomically(&info_path, info.as_bytes())?;
+
+    Ok(Update::Downloaded(problems.len()))
+}
+
+/// Writes a temporary file next to `path` and renames it over `path`.
+fn write_atomically(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let error = |err: std::io::Error| format!("Cannot write {}: {}", path.display(), err);
+    let mut temp_name = path.as_os_str().to_owned();
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp_path = PathBuf::from(temp_name);
+
+    let written = std::fs::File::create(&temp_path).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+    if let Err(err) = written.and_then(|_| std::fs::rename(&temp_path, path)) {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(error(err));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use std::thread::JoinHandle;
+
+    use super::super::testing::TempDir;
+    use super::*;
+
+    /// Answers requests with `responses` in order on a local port. Returns
+    /// the URL and the headers of every request.
+    fn serve(responses: Vec<(&'static str, &'static str, &'static str)>) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/db.yaml", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for (status, headers, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = String::new();
+                let mut reader = BufReader::new(&stream);
+                // Headers end with an empty line
+                while reader.read_line(&mut request).unwrap() > 2 {}
+                requests.push(request);
+                let response = format!(
+                    "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    headers,
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+            requests
+        });
+        (url, server)
+    }
+
+    fn files(dir: &Path) -> Vec<String> {
+        let mut files: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn unchanged_problems_are_not_downloaded_again() {
+        let dir = TempDir::new();
+        let (url, server) = serve(vec![
+            ("200 OK", "ETag: \"v1\"\r\n", EMBEDDED_DB),
+            ("304 Not Modified", "", ""),
+        ]);
+
+        assert!(matches!(update_from(&url, dir.path()), Ok(Update::Downloaded(n)) if n > 0));
+        assert_eq!(std::fs::read_to_string(dir.path().join(DB_FILE)).unwrap(), EMBEDDED_DB);
+        assert!(matches!(update_from(&url, dir.path()), Ok(Update::UpToDate)));
+
+        let requests = server.join().unwrap();
+        assert!(!requests[0].contains("If-None-Match"), "{}", requests[0]);
+        assert!(requests[1].contains("If-None-Match: \"v1\""), "{}", requests[1]);
+        assert_eq!(files(dir.path()), [INFO_FILE, DB_FILE]);
+    }
+
+    #[test]
+    fn invalid_download_keeps_the_cached_problems() {
+        let dir = TempDir::new();
+        dir.write(DB_FILE, "cached");
+        let (url, server) = serve(vec![("200 OK", "", "- name: [")]);
+
+        let err = update_from(&url, dir.path()).map(|_| ()).unwrap_err();
+        assert!(err.starts_with("Downloaded problems are not valid"), "{}", err);
+        assert_eq!(std::fs::read_to_string(dir.path().join(DB_FILE)).unwrap(), "cached");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn write_replaces_the_file_without_leftovers() {
+        let dir = TempDir::new();
+        let path = dir.write(DB_FILE, "old");
+        write_atomically(&path, b"new").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(files(dir.path()), [DB_FILE]);
+
+        let err = write_atomically(&dir.path().join("missing").join(DB_FILE), b"new").unwrap_err();
+        assert!(err.starts_with("Cannot write"), "{}", err);
+        assert_eq!(files(dir.path()), [DB_FILE]);
+    }
+}
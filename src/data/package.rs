@@ -0,0 +1,194 @@
+use std::path::{Path, PathBuf};
+
+use serde_yaml::Value;
+
+use super::validation::{self, Document, Issue};
+use crate::application::common::{Problem, TestCase};
+
+const METADATA_FILE: &str = "problem.yaml";
+const STATEMENT_FILE: &str = "statement.md";
+const TESTS_DIR: &str = "tests";
+
+/// Whether the directory is a single problem package.
+pub fn is_package(dir: &Path) -> bool {
+    dir.join(METADATA_FILE).is_file()
+}
+
+/// Loads every package inside of `dir`, ordered by the names of their
+/// directories. `dir` may also be a package itself. Reports the issues of
+/// all packages at once.
+pub fn load_packages(dir: &Path) -> Result<Vec<Problem>, Vec<Issue>> {
+    if is_package(dir) {
+        return Ok(vec![load_package(dir)?]);
+    }
+
+    let mut packages: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|err| vec![Issue::new(dir.display(), format!("Cannot read the directory: {}", err))])?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| is_package(path))
+        .collect();
+    packages.sort();
+
+    let mut problems = Vec::new();
+    let mut issues = Vec::new();
+    for package in packages {
+        match load_package(&package) {
+            Ok(problem) => problems.push(problem),
+            Err(mut package_issues) => issues.append(&mut package_issues),
+        }
+    }
+    match issues.is_empty() {
+        true => Ok(problems),
+        false => Err(issues),
+    }
+}
+
+/// Loads a problem from its package: the metadata from `problem.yaml`, the
+/// statement from `statement.md` and test cases from `tests/NN.in` and
+/// `tests/NN.out` pairs. Statement and test cases may also be given inline
+/// in `problem.yaml`, test cases from files are added after them.
+pub fn load_package(dir: &Path) -> Result<Problem, Vec<Issue>> {
+    let metadata_path = dir.join(METADATA_FILE);
+    let metadata_file = metadata_path.display().to_string();
+    let metadata = read(&metadata_path).map_err(|err| vec![err])?;
+    let (document, metadata) = Document::parse(&metadata_file, &metadata)?;
+    let mut metadata = match metadata {
+        Value::Mapping(metadata) => metadata,
+        _ => return Err(vec![document.issue("", "Expected a problem with its fields")]),
+    };
+
+    let statement_path = dir.join(STATEMENT_FILE);
+    if statement_path.is_file() {
+        let statement = read(&statement_path).map_err(|err| vec![err])?;
+        metadata.insert("statement".into(), statement.trim_end().into());
+    }
+
+    let test_cases_key = Value::from("test_cases");
+    let mut test_cases = match metadata.remove(&test_cases_key) {
+        Some(Value::Sequence(inline)) => inline,
+        Some(_) => return Err(vec![document.issue("test_cases", "Expected a list of test cases")]),
+        None => Vec::new(),
+    };
+    let first_id = test_cases
+        .iter()
+        .filter_map(|test_case| test_case.get("id").and_then(Value::as_u64))
+        .map(|id| id as usize + 1)
+        .max()
+        .unwrap_or(0);
+    for test_case in load_tests(&dir.join(TESTS_DIR), first_id).map_err(|err| vec![err])? {
+        test_cases.push(serde_yaml::to_value(test_case).map_err(|err| vec![Issue::new(&metadata_file, err)])?);
+    }
+    metadata.insert(test_cases_key, Value::Sequence(test_cases));
+
+    validation::problem(&document, "", Value::Mapping(metadata))
+}
+
+/// Reads `NN.in` and `NN.out` pairs, ordered by `NN`. Ids are given in this
+/// order, starting from `first_id`. Complexity of a test is the size of its
+/// input.
+fn load_tests(dir: &Path, first_id: usize) -> Result<Vec<TestCase>, Issue> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut inputs: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|err| Issue::new(dir.display(), format!("Cannot read the directory: {}", err)))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "in"))
+        .collect();
+    inputs.sort_by_key(|path| {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+        (stem.parse::<u64>().ok(), stem)
+    });
+
+    inputs
+        .iter()
+        .enumerate()
+        .map(|(i, input_path)| {
+            let output_path = input_path.with_extension("out");
+            if !output_path.is_file() {
+                return Err(Issue::new(input_path.display(), "There is no matching .out file"));
+            }
+            let input = read(input_path)?;
+            Ok(TestCase {
+                id: first_id + i,
+                complexity: input.len() as u32,
+                input,
+                output: read(&output_path)?,
+                is_stress_test: false,
+                time_limit_ms: None,
+                reference: None,
+            })
+        })
+        .collect()
+}
+
+fn read(path: &Path) -> Result<String, Issue> {
+    std::fs::read_to_string(path).map_err(|err| Issue::new(path.display(), format!("Cannot read the file: {}", err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::testing::TempDir;
+    use super::*;
+
+    const METADATA: &str = "name: Sum\ndifficulty: Easy\n";
+
+    fn inputs(problem: &Problem) -> Vec<(usize, &str, &str)> {
+        problem.test_cases.iter().map(|tc| (tc.id, tc.input.as_str(), tc.output.as_str())).collect()
+    }
+
+    #[test]
+    fn tests_are_loaded_in_numeric_order() {
+        let dir = TempDir::new();
+        dir.write("problem.yaml", METADATA);
+        dir.write("statement.md", "Add two numbers\n\n");
+        for (n, input) in [(1, "1 2"), (2, "3 4"), (10, "5 6")] {
+            dir.write(&format!("tests/{:02}.in", n), input);
+            dir.write(&format!("tests/{:02}.out", n), &input.len().to_string());
+        }
+
+        let problem = load_package(dir.path()).unwrap();
+        assert_eq!(problem.statement, "Add two numbers");
+        assert_eq!(inputs(&problem), [(0, "1 2", "3"), (1, "3 4", "3"), (2, "5 6", "3")]);
+        assert_eq!(problem.test_cases[0].complexity, 3);
+    }
+
+    #[test]
+    fn tests_from_files_come_after_inline_ones() {
+        let dir = TempDir::new();
+        let inline = "statement: Add\ntest_cases:\n  - id: 0\n    complexity: 1\n    input: '1'\n    output: '1'\n    is_stress_test: false\n";
+        dir.write("problem.yaml", &format!("{METADATA}{inline}"));
+        dir.write("tests/01.in", "2");
+        dir.write("tests/01.out", "2");
+
+        let problem = load_package(dir.path()).unwrap();
+        assert_eq!(inputs(&problem), [(0, "1", "1"), (1, "2", "2")]);
+    }
+
+    #[test]
+    fn input_without_output_is_reported() {
+        let dir = TempDir::new();
+        dir.write("problem.yaml", &format!("{METADATA}statement: Add\n"));
+        dir.write("tests/01.in", "1");
+
+        let issues = load_package(dir.path()).unwrap_err();
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].file.ends_with("01.in"), "{}", issues[0]);
+        assert_eq!(issues[0].message, "There is no matching .out file");
+    }
+
+    #[test]
+    fn directory_of_packages_is_ordered_by_name() {
+        let dir = TempDir::new();
+        for name in ["b", "a"] {
+            dir.write(&format!("{name}/problem.yaml"), &format!("name: {name}\ndifficulty: Easy\nstatement: Add\n"));
+            dir.write(&format!("{name}/tests/1.in"), "1");
+            dir.write(&format!("{name}/tests/1.out"), "1");
+        }
+        dir.write("notes.txt", "not a package");
+
+        let names: Vec<String> = load_packages(dir.path()).unwrap().into_iter().map(|problem| problem.name).collect();
+        assert_eq!(names, ["a", "b"]);
+    }
+}
@@ -0,0 +1,406 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt;
+
+use serde_yaml::{Mapping, Value};
+use yaml_rust::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust::scanner::Marker;
+
+use crate::application::common::Problem;
+
+/// Fields of `Problem`, `TestCase` and `StressTest`. Any other key is most
+/// likely a typo, which serde would silently ignore.
+const PROBLEM_KEYS: &[&str] = &[
+    "name",
+    "statement",
+    "test_cases",
+    "difficulty",
+    "input_mode",
+    "time_limit_ms",
+    "memory_limit_mb",
+    "output_limit_mb",
+    "checker",
+    "interactor",
+    "stress_tests",
+];
+const TEST_CASE_KEYS: &[&str] = &["id", "complexity", "input", "output", "is_stress_test", "time_limit_ms"];
+const STRESS_TEST_KEYS: &[&str] = &["sizes", "input", "output", "reference", "complexity", "time_limit_ms"];
+
+/// Something wrong with a problem source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub file: String,
+    pub line: Option<usize>,
+    /// Path of the field, like `[2].test_cases[0].id`
+    pub field: String,
+    pub message: String,
+}
+
+impl Issue {
+    /// Issue about a whole file.
+    pub fn new(file: impl ToString, message: impl ToString) -> Self {
+        Self {
+            file: file.to_string(),
+            line: None,
+            field: String::new(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file)?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+        }
+        if !self.field.is_empty() {
+            write!(f, ": {}", self.field)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Parsed YAML file, which remembers on which line every field is.
+pub struct Document {
+    file: String,
+    lines: HashMap<String, usize>,
+}
+
+impl Document {
+    pub fn parse(file: &str, text: &str) -> Result<(Self, Value), Vec<Issue>> {
+        let value: Value = serde_yaml::from_str(text).map_err(|err| {
+            vec![Issue {
+                line: err.location().map(|location| location.line()),
+                ..Issue::new(file, err)
+            }]
+        })?;
+
+        let mut index = LineIndex::default();
+        Parser::new(text.chars())
+            .load(&mut index, false)
+            .map_err(|err| vec![Issue::new(file, err)])?;
+
+        let document = Self {
+            file: file.to_string(),
+            lines: index.lines,
+        };
+        Ok((document, value))
+    }
+
+    /// Issue about the field at `path`. It is reported on the line of the
+    /// field, or of the closest parent which is in the file.
+    pub fn issue(&self, path: &str, message: impl ToString) -> Issue {
+        let mut prefix = path;
+        let line = loop {
+            if let Some(line) = self.lines.get(prefix) {
+                break Some(*line);
+            }
+            match prefix.rfind(['.', '[']) {
+                Some(end) => prefix = &prefix[..end],
+                None if !prefix.is_empty() => prefix = "",
+                None => break None,
+            }
+        };
+
+        Issue {
+            line,
+            field: path.to_string(),
+            ..Issue::new(&self.file, message)
+        }
+    }
+}
+
+/// Checks every problem of a YAML file with a list of them, and loads them
+/// if there is nothing wrong.
+pub fn problems(file: &str, text: &str) -> Result<Vec<Problem>, Vec<Issue>> {
+    let (document, value) = Document::parse(file, text)?;
+    let values = match value {
+        Value::Sequence(values) => values,
+        _ => return Err(vec![document.issue("", "Expected a list of problems")]),
+    };
+
+    let mut problems = Vec::new();
+    let mut issues = Vec::new();
+    for (i, value) in values.into_iter().enumerate() {
+        match problem(&document, &format!("[{}]", i), value) {
+            Ok(problem) => problems.push(problem),
+            Err(mut problem_issues) => issues.append(&mut problem_issues),
+        }
+    }
+
+    match issues.is_empty() {
+        true => Ok(problems),
+        false => Err(issues),
+    }
+}
+
+/// Checks the problem at `path` of the document, and loads it if there is
+/// nothing wrong.
+pub fn problem(document: &Document, path: &str, value: Value) -> Result<Problem, Vec<Issue>> {
+    let mapping = match value.as_mapping() {
+        Some(mapping) => mapping,
+        None => return Err(vec![document.issue(path, "Expected a problem with its fields")]),
+    };
+
+    let mut issues = Vec::new();
+    check_keys(document, path, mapping, PROBLEM_KEYS, &mut issues);
+
+    match get(mapping, "statement") {
+        None => issues.push(document.issue(path, "Missing statement")),
+        Some(Value::String(statement)) if statement.trim().is_empty() => {
+            issues.push(document.issue(&field(path, "statement"), "Statement is empty"))
+        }
+        _ => {}
+    }
+
+    let test_cases_path = field(path, "test_cases");
+    match get(mapping, "test_cases") {
+        None => issues.push(document.issue(path, "Missing test_cases")),
+        Some(Value::Sequence(test_cases)) if test_cases.is_empty() => {
+            issues.push(document.issue(&test_cases_path, "There are no test cases"))
+        }
+        Some(Value::Sequence(test_cases)) => check_test_cases(document, &test_cases_path, test_cases, &mut issues),
+        Some(_) => {}
+    }
+
+    if let Some(Value::Sequence(stress_tests)) = get(mapping, "stress_tests") {
+        for (i, stress_test) in stress_tests.iter().enumerate() {
+            if let Some(stress_test) = stress_test.as_mapping() {
+                let stress_test_path = format!("{}[{}]", field(path, "stress_tests"), i);
+                check_keys(document, &stress_test_path, stress_test, STRESS_TEST_KEYS, &mut issues);
+            }
+        }
+    }
+
+    // Wrong types are left to serde, once the rest is fine
+    if !issues.is_empty() {
+        return Err(issues);
+    }
+    serde_yaml::from_value(value).map_err(|err| vec![document.issue(path, err)])
+}
+
+/// Ids are indices of the test cases, so they must be unique and go from 0
+/// without gaps, in any order.
+fn check_test_cases(document: &Document, path: &str, test_cases: &[Value], issues: &mut Vec<Issue>) {
+    let n = test_cases.len() as u64;
+    let mut indices_by_id = HashMap::new();
+
+    for (i, test_case) in test_cases.iter().enumerate() {
+        let test_case_path = format!("{}[{}]", path, i);
+        let test_case = match test_case.as_mapping() {
+            Some(test_case) => test_case,
+            None => continue,
+        };
+        check_keys(document, &test_case_path, test_case, TEST_CASE_KEYS, issues);
+
+        let id = match get(test_case, "id").and_then(Value::as_u64) {
+            Some(id) => id,
+            None => continue,
+        };
+        let id_path = field(&test_case_path, "id");
+        match indices_by_id.entry(id) {
+            Entry::Occupied(entry) => issues.push(document.issue(
+                &id_path,
+                format!("Duplicate id {}, already used by {}[{}]", id, path, entry.get()),
+            )),
+            Entry::Vacant(entry) => {
+                entry.insert(i);
+            }
+        }
+        if id >= n {
+            let message = format!("Id {} is too big, ids must go from 0 to {}", id, n - 1);
+            issues.push(document.issue(&id_path, message));
+        }
+    }
+
+    let missing: Vec<String> = (0..n)
+        .filter(|id| !indices_by_id.contains_key(id))
+        .map(|id| id.to_string())
+        .collect();
+    if !missing.is_empty() {
+        let message = format!("Ids must go from 0 to {} without gaps, missing {}", n - 1, missing.join(", "));
+        issues.push(document.issue(path, message));
+    }
+}
+
+fn check_keys(document: &Document, path: &str, mapping: &Mapping, known: &[&str], issues: &mut Vec<Issue>) {
+    for (key, _) in mapping.iter() {
+        match key.as_str() {
+            Some(key) if known.contains(&key) => {}
+            Some(key) => issues.push(document.issue(&field(path, key), format!("Unknown key `{}`", key))),
+            None => issues.push(document.issue(path, format!("Unknown key {:?}", key))),
+        }
+    }
+}
+
+fn get<'a>(mapping: &'a Mapping, key: &str) -> Option<&'a Value> {
+    mapping.get(&Value::from(key))
+}
+
+fn field(path: &str, key: &str) -> String {
+    match path.is_empty() {
+        true => key.to_string(),
+        false => format!("{}.{}", path, key),
+    }
+}
+
+/// Collects the line of every node while the YAML file is parsed, by its
+/// path. A field is on the line of its key.
+#[derive(Default)]
+struct LineIndex {
+    lines: HashMap<String, usize>,
+    /// Collections the parser is inside of, with their paths
+    stack: Vec<(String, Frame)>,
+}
+
+enum Frame {
+    /// Index of the next item
+    Sequence(usize),
+    /// Key of the next value, `None` while the key is expected
+    Mapping(Option<String>),
+}
+
+impl LineIndex {
+    fn path(&self) -> String {
+        match self.stack.last() {
+            None => String::new(),
+            Some((path, Frame::Sequence(i))) => format!("{}[{}]", path, i),
+            Some((path, Frame::Mapping(Some(key)))) => field(path, key),
+            Some((path, Frame::Mapping(None))) => path.clone(),
+        }
+    }
+
+    fn node_finished(&mut self) {
+        match self.stack.last_mut() {
+            Some((_, Frame::Sequence(i))) => *i += 1,
+            Some((_, Frame::Mapping(key @ Some(_)))) => *key = None,
+            // Key which is not a scalar, the value comes next
+            Some((_, Frame::Mapping(key @ None))) => *key = Some(String::new()),
+            None => {}
+        }
+    }
+}
+
+impl MarkedEventReceiver for LineIndex {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        match event {
+            Event::Scalar(text, ..) => {
+                if let Some((_, Frame::Mapping(key @ None))) = self.stack.last_mut() {
+                    *key = Some(text);
+                    let path = self.path();
+                    self.lines.entry(path).or_insert(mark.line());
+                    return;
+                }
+                self.lines.entry(self.path()).or_insert(mark.line());
+                self.node_finished();
+            }
+            Event::Alias(_) => {
+                self.lines.entry(self.path()).or_insert(mark.line());
+                self.node_finished();
+            }
+            Event::SequenceStart(_) | Event::MappingStart(_) => {
+                let path = self.path();
+                self.lines.entry(path.clone()).or_insert(mark.line());
+                let frame = match event {
+                    Event::SequenceStart(_) => Frame::Sequence(0),
+                    _ => Frame::Mapping(None),
+                };
+                self.stack.push((path, frame));
+            }
+            Event::SequenceEnd | Event::MappingEnd => {
+                self.stack.pop();
+                self.node_finished();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::common::{Checker, Difficulty, InputMode, TestCase};
+    use crate::application::stress_test::{Formula, StressTest, Template};
+
+    fn keys(value: &Value) -> Vec<String> {
+        let mut keys: Vec<String> = value
+            .as_mapping()
+            .expect("a mapping")
+            .iter()
+            .filter_map(|(key, _)| key.as_str().map(str::to_string))
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    fn sorted(keys: &[&str]) -> Vec<String> {
+        let mut keys: Vec<String> = keys.iter().map(|key| key.to_string()).collect();
+        keys.sort();
+        keys
+    }
+
+    /// Every field of the problem is set, so that a new one breaks the build
+    /// here until it is added to the known keys. Test cases serialize all of
+    /// their fields anyway.
+    fn full_problem() -> Problem {
+        Problem {
+            name: "Sum".to_string(),
+            statement: "Add two numbers".to_string(),
+            test_cases: vec![TestCase {
+                input: "1 2".to_string(),
+                output: "3".to_string(),
+                time_limit_ms: Some(100),
+                ..TestCase::default()
+            }],
+            difficulty: Difficulty::Easy,
+            input_mode: InputMode::Stdin,
+            time_limit_ms: Some(1000),
+            memory_limit_mb: Some(256),
+            output_limit_mb: Some(64),
+            checker: Checker::Exact,
+            interactor: Some("./interactor".to_string()),
+            stress_tests: vec![StressTest {
+                sizes: vec![10],
+                input: Template::Text("1 2".to_string()),
+                output: Some(Template::Text("3".to_string())),
+                reference: Some("./reference".to_string()),
+                complexity: Some(Formula::parse("n").unwrap()),
+                time_limit_ms: Some(100),
+            }],
+            source: String::new(),
+        }
+    }
+
+    #[test]
+    fn known_keys_match_the_fields() {
+        let value = serde_yaml::to_value(full_problem()).unwrap();
+        assert_eq!(keys(&value), sorted(PROBLEM_KEYS));
+        assert_eq!(keys(&value["test_cases"][0]), sorted(TEST_CASE_KEYS));
+        assert_eq!(keys(&value["stress_tests"][0]), sorted(STRESS_TEST_KEYS));
+    }
+
+    #[test]
+    fn full_problem_is_valid() {
+        let text = serde_yaml::to_string(&vec![full_problem()]).unwrap();
+        let problems = problems("test.yaml", &text).unwrap();
+        assert_eq!(problems, vec![full_problem()]);
+    }
+
+    #[test]
+    fn unknown_key_is_reported_on_its_line() {
+        let text = [
+            "- name: Sum",
+            "  statement: Add",
+            "  difficulty: Easy",
+            "  time_limit: 5",
+            "  test_cases:",
+            "    - {id: 0, complexity: 1, input: a, output: b, is_stress_test: false}",
+        ]
+        .join("\n");
+        let issues = problems("test.yaml", &text).unwrap_err();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].line, Some(4));
+        assert_eq!(issues[0].field, "[0].time_limit");
+    }
+}
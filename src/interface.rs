@@ -7,7 +7,6 @@ use crate::application::ui::ProblemMenuLayout;
 use crate::application::ui::SelectScreenLayout;
 use crate::application::ui::UIElement;
 use crossterm::event::Event;
-use tui::widgets::Paragraph;
 use tui::{backend::Backend, Frame};
 
 use crate::application::AppState;
@@ -50,6 +49,10 @@ impl AppState {
     }
 
     pub fn react_to_code_runner(&mut self) -> AfterEvent {
+        if self.controller.react_to_file_changes() == AfterEvent::DoRefresh {
+            return AfterEvent::DoRefresh;
+        }
+
         match self.view.check_for_changes() {
             true => AfterEvent::DoRefresh,
             false => AfterEvent::NoRefresh,
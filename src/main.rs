@@ -19,12 +19,18 @@ mod interface;
 mod data;
 
 use application::AppState;
-use arguments::AppArgs;
+use arguments::{Action, AppArgs};
 
 const EVENT_CHECK_DUR: Duration = Duration::from_millis(300);
 
 fn main() {
     let args = AppArgs::parse();
+    match args.sub {
+        Action::Update => return update(),
+        Action::Validate => return validate(&args),
+        Action::Run => {}
+    }
+
     let mut app = AppState::from(args);
 
     enable_raw_mode().unwrap();
@@ -44,6 +50,39 @@ fn main() {
     result.unwrap();
 }
 
+/// Downloads the problems into the local cache.
+fn update() {
+    match data::update() {
+        Ok(data::Update::Downloaded(n_problems)) => println!("Downloaded {} problems", n_problems),
+        Ok(data::Update::UpToDate) => println!("Problems are already up to date"),
+        Err(err_msg) => {
+            eprintln!("{}", err_msg);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Prints every issue of the problems in all sources, and exits with an
+/// error if there are any.
+fn validate(args: &AppArgs) {
+    let mut valid = true;
+    for source in application::settings_from(args).sources {
+        match data::validate(&source) {
+            Ok(n_problems) => println!("{}: no issues found in {} problems", source, n_problems),
+            Err(issues) => {
+                for issue in &issues {
+                    println!("{}", issue);
+                }
+                eprintln!("{}: issues found: {}", source, issues.len());
+                valid = false;
+            }
+        }
+    }
+    if !valid {
+        std::process::exit(1);
+    }
+}
+
 fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut AppState) -> io::Result<()> {
     let mut action = DoRefresh;
     while action != Quit {